@@ -0,0 +1,49 @@
+// ハッシュ索引による Object::get と、メンバーを先頭から走査する方法の比較
+//
+//     cargo bench --bench object_lookup
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use json_parser::{Element, Number, Object};
+
+fn bench<F: FnMut()>(name: &str, iterations: u32, mut f: F) {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed();
+
+    println!("{:<28} {:>12?} / iter", name, elapsed / iterations.max(1));
+}
+
+fn linear_get<'a, 'b>(object: &'a Object<'b>, key: &str) -> Option<&'a Element<'b>> {
+    object
+        .members()
+        .iter()
+        .find(|member| member.key() == key)
+        .map(|member| member.value())
+}
+
+fn main() {
+    for size in [10, 100, 1_000, 10_000] {
+        let object: Object = (0..size)
+            .map(|i| (format!("key{}", i), Element::Number(Number::from(i))))
+            .collect();
+        let keys: Vec<String> = (0..size).map(|i| format!("key{}", i)).collect();
+
+        println!("{} keys", size);
+
+        bench("  hash index (get)", 100, || {
+            for key in &keys {
+                black_box(object.get(black_box(key)));
+            }
+        });
+
+        bench("  linear scan (Vec)", 100, || {
+            for key in &keys {
+                black_box(linear_get(&object, black_box(key)));
+            }
+        });
+    }
+}
@@ -0,0 +1,586 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Index;
+use std::slice;
+
+use crate::number::Number;
+
+// 存在しないキーやインデックスを参照したときに返す値
+static NULL: Element<'static> = Element::Null;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Member<'a> {
+    pub(crate) key: Cow<'a, str>,
+    pub(crate) value: Element<'a>,
+}
+
+impl<'a> Member<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn value(&self) -> &Element<'a> {
+        &self.value
+    }
+
+    pub fn value_mut(&mut self) -> &mut Element<'a> {
+        &mut self.value
+    }
+
+    pub fn into_owned(self) -> Member<'static> {
+        Member {
+            key: Cow::Owned(self.key.into_owned()),
+            value: self.value.into_owned(),
+        }
+    }
+}
+
+/// JSON object that keeps members in source order.
+///
+/// A hash index from key to position sits next to the member list, so
+/// lookups don't have to scan every member.
+#[derive(Clone, Default)]
+pub struct Object<'a> {
+    members: Vec<Member<'a>>,
+    // キーから最初に現れた位置への索引
+    index: HashMap<Cow<'a, str>, usize>,
+}
+
+impl<'a> Object<'a> {
+    pub fn new() -> Self {
+        Object::default()
+    }
+
+    // 重複したキーもそのまま保持する
+    #[cfg(test)]
+    pub(crate) fn from_members(members: Vec<Member<'a>>) -> Self {
+        let mut object = Object {
+            members: Vec::with_capacity(members.len()),
+            index: HashMap::with_capacity(members.len()),
+        };
+
+        for member in members {
+            object.push(member);
+        }
+
+        object
+    }
+
+    // キーが重複していても末尾に追加する
+    pub(crate) fn push(&mut self, member: Member<'a>) {
+        if !self.index.contains_key(&member.key) {
+            self.index.insert(member.key.clone(), self.members.len());
+        }
+
+        self.members.push(member);
+    }
+
+    pub fn members(&self) -> &[Member<'a>] {
+        &self.members
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Element<'a>> {
+        self.position(key).map(|i| &self.members[i].value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Element<'a>> {
+        self.position(key).map(|i| &mut self.members[i].value)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// Sets the value for `key`, returning the previous value.
+    ///
+    /// An existing key keeps its position; a new key is appended.
+    pub fn insert(
+        &mut self,
+        key: impl Into<Cow<'a, str>>,
+        value: Element<'a>,
+    ) -> Option<Element<'a>> {
+        let key = key.into();
+
+        match self.position(&key) {
+            Some(i) => Some(std::mem::replace(&mut self.members[i].value, value)),
+            None => {
+                self.index.insert(key.clone(), self.members.len());
+                self.members.push(Member { key, value });
+                None
+            }
+        }
+    }
+
+    /// Removes `key`, keeping the order of the remaining members.
+    ///
+    /// This shifts the members after it, so it is O(n).
+    pub fn remove(&mut self, key: &str) -> Option<Element<'a>> {
+        let i = self.position(key)?;
+        let member = self.members.remove(i);
+
+        // 後ろの要素の位置がずれるので索引を詰める
+        self.index.remove(key);
+        for pos in self.index.values_mut() {
+            if *pos > i {
+                *pos -= 1;
+            }
+        }
+
+        // 同じキーが後ろにも残っていればそちらを指す
+        if let Some(j) = self.members[i..].iter().position(|m| m.key == key) {
+            self.index.insert(member.key, i + j);
+        }
+
+        Some(member.value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Element<'a>)> {
+        self.members
+            .iter()
+            .map(|member| (member.key(), member.value()))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.members.iter().map(|member| member.key())
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Element<'a>> {
+        self.members.iter().map(|member| member.value())
+    }
+
+    // 同じキーが複数ある場合は最初のものを返す
+    pub(crate) fn position(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    pub fn into_owned(self) -> Object<'static> {
+        Object {
+            members: self.members.into_iter().map(Member::into_owned).collect(),
+            index: self
+                .index
+                .into_iter()
+                .map(|(key, i)| (Cow::Owned(key.into_owned()), i))
+                .collect(),
+        }
+    }
+}
+
+// 索引はmembersから決まるので比較や表示には使わない
+impl PartialEq for Object<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.members == other.members
+    }
+}
+
+impl fmt::Debug for Object<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Object")
+            .field("members", &self.members)
+            .finish()
+    }
+}
+
+impl<'a, K: Into<Cow<'a, str>>> FromIterator<(K, Element<'a>)> for Object<'a> {
+    fn from_iter<T: IntoIterator<Item = (K, Element<'a>)>>(iter: T) -> Self {
+        let mut object = Object::new();
+
+        for (key, value) in iter {
+            object.insert(key, value);
+        }
+
+        object
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s Object<'a> {
+    type Item = &'s Member<'a>;
+    type IntoIter = slice::Iter<'s, Member<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.members.iter()
+    }
+}
+
+impl<'a> Index<&str> for Object<'a> {
+    type Output = Element<'a>;
+
+    fn index(&self, key: &str) -> &Element<'a> {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Array<'a> {
+    pub(crate) elements: Vec<Element<'a>>,
+}
+
+impl<'a> Array<'a> {
+    pub fn new() -> Self {
+        Array::default()
+    }
+
+    pub fn elements(&self) -> &[Element<'a>] {
+        &self.elements
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Element<'a>> {
+        self.elements.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Element<'a>> {
+        self.elements.get_mut(index)
+    }
+
+    pub fn push(&mut self, element: Element<'a>) {
+        self.elements.push(element);
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, Element<'a>> {
+        self.elements.iter()
+    }
+
+    pub fn into_owned(self) -> Array<'static> {
+        Array {
+            elements: self.elements.into_iter().map(Element::into_owned).collect(),
+        }
+    }
+}
+
+impl<'a> FromIterator<Element<'a>> for Array<'a> {
+    fn from_iter<T: IntoIterator<Item = Element<'a>>>(iter: T) -> Self {
+        Array {
+            elements: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'s, 'a> IntoIterator for &'s Array<'a> {
+    type Item = &'s Element<'a>;
+    type IntoIter = slice::Iter<'s, Element<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl<'a> Index<usize> for Array<'a> {
+    type Output = Element<'a>;
+
+    fn index(&self, index: usize) -> &Element<'a> {
+        self.get(index).unwrap_or(&NULL)
+    }
+}
+
+/// A JSON value.
+///
+/// Strings and keys borrow from the source text where no escapes had to be
+/// decoded; `into_owned` detaches the element from the source.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Element<'a> {
+    String(Cow<'a, str>),
+    Number(Number),
+    Boolean(bool),
+    Null,
+    Object(Object<'a>),
+    Array(Array<'a>),
+}
+
+impl<'a> Element<'a> {
+    /// Looks up `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&Element<'a>> {
+        self.as_object().and_then(|object| object.get(key))
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Element<'a>> {
+        self.as_object_mut().and_then(|object| object.get_mut(key))
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.as_object()
+            .is_some_and(|object| object.contains_key(key))
+    }
+
+    /// Number of members or elements; 0 for scalars.
+    pub fn len(&self) -> usize {
+        match self {
+            Element::Object(object) => object.len(),
+            Element::Array(array) => array.len(),
+            _ => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Element::String(str) => Some(str),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Element::Number(num) => Some(num),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().and_then(Number::as_f64)
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(Number::as_i64)
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(Number::as_u64)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Element::Boolean(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Array<'a>> {
+        match self {
+            Element::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Array<'a>> {
+        match self {
+            Element::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&Object<'a>> {
+        match self {
+            Element::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Object<'a>> {
+        match self {
+            Element::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Element::Null)
+    }
+
+    pub fn into_owned(self) -> Element<'static> {
+        match self {
+            Element::String(str) => Element::String(Cow::Owned(str.into_owned())),
+            Element::Number(num) => Element::Number(num),
+            Element::Boolean(val) => Element::Boolean(val),
+            Element::Null => Element::Null,
+            Element::Object(object) => Element::Object(object.into_owned()),
+            Element::Array(array) => Element::Array(array.into_owned()),
+        }
+    }
+}
+
+impl<'a> Index<&str> for Element<'a> {
+    type Output = Element<'a>;
+
+    fn index(&self, key: &str) -> &Element<'a> {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl<'a> Index<usize> for Element<'a> {
+    type Output = Element<'a>;
+
+    fn index(&self, index: usize) -> &Element<'a> {
+        self.as_array()
+            .and_then(|array| array.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> Element<'static> {
+        crate::parse(
+            r#"{"name": "山田太郎", "age": 30, "admin": false, "hobbies": ["読書", "映画"], "address": {"city": "東京"}, "none": null}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn accessors() {
+        let element = sample();
+
+        assert_eq!(element["name"].as_str(), Some("山田太郎"));
+        assert_eq!(element["age"].as_u64(), Some(30));
+        assert_eq!(element["age"].as_f64(), Some(30.0));
+        assert_eq!(element["admin"].as_bool(), Some(false));
+        assert_eq!(element["hobbies"][1].as_str(), Some("映画"));
+        assert_eq!(element["address"]["city"].as_str(), Some("東京"));
+        assert!(element["none"].is_null());
+
+        assert_eq!(element.len(), 6);
+        assert_eq!(element["hobbies"].len(), 2);
+        assert!(element.contains_key("none"));
+        assert!(!element.contains_key("missing"));
+        assert_eq!(element.get("missing"), None);
+        assert_eq!(element["name"].as_array(), None);
+        assert!(element["address"].as_object().is_some());
+    }
+
+    #[test]
+    fn index_miss_returns_null() {
+        let element = sample();
+
+        assert!(element["missing"].is_null());
+        assert!(element["missing"]["deeper"].is_null());
+        assert!(element["hobbies"][10].is_null());
+        assert!(element["name"][0].is_null());
+        assert!(element[0].is_null());
+    }
+
+    #[test]
+    fn get_mut() {
+        let mut element = sample();
+
+        *element.get_mut("age").unwrap() = Element::Number(Number::from(31));
+        assert_eq!(element["age"].as_i64(), Some(31));
+
+        element
+            .get_mut("hobbies")
+            .and_then(Element::as_array_mut)
+            .unwrap()
+            .push(Element::String("旅行".into()));
+        assert_eq!(element["hobbies"][2].as_str(), Some("旅行"));
+
+        assert_eq!(element.get_mut("missing"), None);
+    }
+
+    #[test]
+    fn iterate() {
+        let element = sample();
+
+        let keys: Vec<&str> = element.as_object().unwrap().keys().collect();
+        assert_eq!(
+            keys,
+            vec!["name", "age", "admin", "hobbies", "address", "none"]
+        );
+
+        let hobbies: Vec<&str> = element["hobbies"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(Element::as_str)
+            .collect();
+        assert_eq!(hobbies, vec!["読書", "映画"]);
+
+        let mut count = 0;
+        for member in element.as_object().unwrap() {
+            assert_eq!(element.get(member.key()), Some(member.value()));
+            count += 1;
+        }
+        assert_eq!(count, 6);
+    }
+
+    #[test]
+    fn insert_and_remove_keep_order() {
+        let mut object = Object::new();
+
+        assert_eq!(object.insert("a".to_string(), Element::Null), None);
+        assert_eq!(object.insert("b".to_string(), Element::Boolean(true)), None);
+        assert_eq!(object.insert("c".to_string(), Element::Null), None);
+
+        // 既存のキーは位置を変えずに値を置き換える
+        assert_eq!(
+            object.insert("a".to_string(), Element::Boolean(false)),
+            Some(Element::Null)
+        );
+        assert_eq!(object.keys().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert_eq!(object["a"], Element::Boolean(false));
+
+        assert_eq!(object.remove("b"), Some(Element::Boolean(true)));
+        assert_eq!(object.remove("b"), None);
+        assert_eq!(object.keys().collect::<Vec<_>>(), vec!["a", "c"]);
+        assert_eq!(object.len(), 2);
+        assert_eq!(object["c"], Element::Null);
+    }
+
+    #[test]
+    fn large_object_lookup() {
+        let object: Object = (0..10_000)
+            .map(|i| (format!("key{}", i), Element::Number(Number::from(i))))
+            .collect();
+
+        assert_eq!(object.len(), 10_000);
+        assert_eq!(object["key9999"].as_u64(), Some(9999));
+        assert_eq!(object.keys().nth(1234), Some("key1234"));
+
+        let json = Element::Object(object.clone()).to_string();
+        assert!(json.starts_with(r#"{"key0":0,"key1":1,"#));
+        assert_eq!(crate::parse(&json).unwrap(), Element::Object(object));
+    }
+
+    #[test]
+    fn into_owned() {
+        let source = r#"{"name": "山田", "tags": ["a\tb"]}"#.to_string();
+
+        let element = crate::parse(&source).unwrap();
+        assert!(matches!(
+            element["name"],
+            Element::String(Cow::Borrowed("山田"))
+        ));
+
+        let owned: Element<'static> = element.clone().into_owned();
+        drop(source);
+
+        assert!(matches!(&owned["name"], Element::String(Cow::Owned(str)) if str == "山田"));
+        assert_eq!(owned["tags"][0].as_str(), Some("a\tb"));
+        assert_eq!(owned.get("name").and_then(Element::as_str), Some("山田"));
+    }
+
+    #[test]
+    fn duplicate_keys_from_source() {
+        let element = crate::parse(r#"{"a": 1, "b": 2, "a": 3}"#).unwrap();
+
+        // 重複したキーはそのまま残り、最初の値が参照される
+        assert_eq!(element.len(), 3);
+        assert_eq!(element["a"].as_u64(), Some(1));
+        assert_eq!(element.to_string(), r#"{"a":1,"b":2,"a":3}"#);
+
+        let mut object = element.as_object().unwrap().clone();
+        assert_eq!(object.remove("a").and_then(|a| a.as_u64()), Some(1));
+        assert_eq!(object["a"].as_u64(), Some(3));
+        assert_eq!(object["b"].as_u64(), Some(2));
+        assert_eq!(object.remove("a").and_then(|a| a.as_u64()), Some(3));
+        assert!(!object.contains_key("a"));
+    }
+}
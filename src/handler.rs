@@ -0,0 +1,51 @@
+use std::borrow::Cow;
+
+use crate::number::Number;
+use crate::parser::ParseError;
+use crate::span::Span;
+
+/// Receives the values of a document as the parser reads them, without
+/// building an `Element` tree. See `Parser::parse_with`.
+///
+/// Every callback does nothing by default. Returning an error stops the
+/// parser, which passes it back to the caller.
+pub trait JsonHandler<'a> {
+    type Error: From<ParseError>;
+
+    fn start_object(&mut self, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    // メンバーの値の直前に呼ばれる
+    fn key(&mut self, _key: Cow<'a, str>, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn end_object(&mut self, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn start_array(&mut self, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn end_array(&mut self, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn string(&mut self, _value: Cow<'a, str>, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn number(&mut self, _value: Number, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn bool(&mut self, _value: bool, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn null(&mut self, _span: Span) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+pub mod element;
+pub mod handler;
+pub mod limits;
+pub mod lines;
+pub mod number;
+pub mod parser;
+pub mod pretty;
+pub mod pull;
+mod reader;
+pub mod seq;
+pub mod serializer;
+pub mod span;
+pub mod stream;
+#[cfg(test)]
+mod test_util;
+pub mod tokenizer;
+
+use std::{fmt, io};
+
+pub use crate::element::{Array, Element, Member, Object};
+pub use crate::handler::JsonHandler;
+pub use crate::limits::{Limits, ParserLimits, TokenizerLimits};
+pub use crate::lines::{
+    JsonLinesError, JsonLinesOptions, JsonLinesReader, JsonLinesWriter, LineErrorPolicy,
+};
+pub use crate::number::Number;
+pub use crate::parser::{DuplicateKeyPolicy, ParseError, Parser, ParserOptions};
+pub use crate::pretty::{Indent, PrettyPrinter};
+pub use crate::pull::{Event, JsonReader};
+pub use crate::seq::JsonSeqReader;
+pub use crate::span::{Excerpt, Span};
+pub use crate::stream::{ArrayStream, Documents};
+pub use crate::tokenizer::{Token, Tokenizer, TokenizerError, TokenizerOptions, TokenizerTrait};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Error {
+    Tokenize(TokenizerError),
+    Parse(ParseError),
+}
+
+impl Error {
+    pub fn span(&self) -> Span {
+        match self {
+            Error::Tokenize(err) => err.span(),
+            Error::Parse(err) => err.span(),
+        }
+    }
+
+    /// Pairs the error with the text it came from so that `Display` also
+    /// renders the offending source line.
+    pub fn with_source<'a>(&'a self, source: &'a str) -> WithSource<'a> {
+        WithSource {
+            error: self,
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Tokenize(err) => err.fmt(f),
+            Error::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub struct WithSource<'a> {
+    error: &'a Error,
+    source: &'a str,
+}
+
+impl fmt::Display for WithSource<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.error)?;
+        write!(f, "{}", self.error.span().excerpt(self.source))
+    }
+}
+
+impl From<TokenizerError> for Error {
+    fn from(err: TokenizerError) -> Self {
+        Error::Tokenize(err)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(err: ParseError) -> Self {
+        match err {
+            ParseError::Tokenize(err) => Error::Tokenize(err),
+            err => Error::Parse(err),
+        }
+    }
+}
+
+/// Tokenizes and parses `str` into a single JSON element in one pass.
+pub fn parse(str: &str) -> Result<Element<'_>, Error> {
+    let element = Parser::new(Tokenizer::new(str)).parse()?;
+
+    Ok(element)
+}
+
+/// Parses untrusted text, applying every limit in `limits` to both the
+/// tokenizer and the parser.
+pub fn parse_with_limits(str: &str, limits: Limits) -> Result<Element<'_>, Error> {
+    let (tokenizer_options, parser_options) = limited_options(limits);
+    let tokens = Tokenizer::with_options(str, tokenizer_options);
+    let element = Parser::with_options(tokens, parser_options).parse()?;
+
+    Ok(element)
+}
+
+/// Parses a single JSON element from bytes that have not been checked to be
+/// UTF-8, such as a network buffer.
+pub fn parse_slice(bytes: &[u8]) -> Result<Element<'_>, Error> {
+    let element = Parser::new(Tokenizer::from_slice(bytes)).parse()?;
+
+    Ok(element)
+}
+
+/// Parses a single JSON element from `reader`, decoding it incrementally
+/// instead of reading it all into memory first.
+pub fn parse_reader<'a, R: io::Read + 'a>(reader: R) -> Result<Element<'a>, Error> {
+    let element = Parser::new(Tokenizer::from_reader(reader)).parse()?;
+
+    Ok(element)
+}
+
+/// Like `parse_with_limits`, but reads from `reader`. Reading stops as soon
+/// as `max_input_bytes` is exceeded.
+pub fn parse_reader_with_limits<'a, R: io::Read + 'a>(
+    reader: R,
+    limits: Limits,
+) -> Result<Element<'a>, Error> {
+    let (tokenizer_options, parser_options) = limited_options(limits);
+    let tokens = Tokenizer::from_reader_with_options(reader, tokenizer_options);
+    let element = Parser::with_options(tokens, parser_options).parse()?;
+
+    Ok(element)
+}
+
+// limitsをトークナイザーとパーサーに振り分ける
+fn limited_options(limits: Limits) -> (TokenizerOptions, ParserOptions) {
+    let tokenizer_options = TokenizerOptions {
+        limits: limits.tokenizer(),
+        ..TokenizerOptions::default()
+    };
+    let parser_options = ParserOptions {
+        limits: limits.parser(),
+        ..ParserOptions::default()
+    };
+
+    (tokenizer_options, parser_options)
+}
+
+/// Reads a top-level array from `reader`, yielding each element as soon as
+/// it is complete instead of building the whole array.
+pub fn parse_array_stream<'a, R: io::Read + 'a>(reader: R) -> ArrayStream<Tokenizer<'a>> {
+    ArrayStream::new(Tokenizer::from_reader(reader))
+}
+
+/// Reads concatenated documents such as `{..}{..}{..}` from `reader`,
+/// yielding each one with the byte offset it starts at.
+pub fn parse_documents<'a, R: io::Read + 'a>(reader: R) -> Documents<Tokenizer<'a>> {
+    Parser::new(Tokenizer::from_reader(reader)).into_documents()
+}
+
+/// Reads an RFC 7464 JSON text sequence from `reader`, yielding the element
+/// of each record and skipping the ones that fail to parse.
+pub fn parse_json_seq<'a, R: io::Read + 'a>(reader: R) -> JsonSeqReader<Tokenizer<'a>> {
+    let options = TokenizerOptions {
+        json_seq: true,
+        ..TokenizerOptions::default()
+    };
+
+    JsonSeqReader::new(Tokenizer::from_reader_with_options(reader, options))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pretty::PrettyPrinter;
+    use crate::test_util::{Rng, Trickle};
+
+    #[test]
+    fn parse_facade() {
+        let result = parse(r#"{"key": [1, "two", null]}"#).unwrap();
+
+        let object = match result {
+            Element::Object(object) => object,
+            _ => panic!("expected object"),
+        };
+
+        assert_eq!(object.members().len(), 1);
+        assert_eq!(object.members()[0].key(), "key");
+
+        let array = match object.members()[0].value() {
+            Element::Array(array) => array,
+            _ => panic!("expected array"),
+        };
+
+        assert_eq!(
+            array.elements(),
+            &[
+                Element::Number(Number::from(1)),
+                Element::String("two".into()),
+                Element::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_facade_tokenize_error() {
+        assert_eq!(
+            parse("nul!"),
+            Err(Error::Tokenize(TokenizerError::InvalidCharacter {
+                character: '!',
+                span: Span {
+                    start: 3,
+                    end: 4,
+                    line: 1,
+                    column: 4
+                }
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_facade_limits() {
+        let limits = Limits {
+            max_input_bytes: Some(16),
+            max_nodes: Some(3),
+            ..Limits::default()
+        };
+
+        assert!(parse_with_limits("[1, 2]", limits).is_ok());
+        assert!(matches!(
+            parse_with_limits("[1, 2, 3]", limits),
+            Err(Error::Parse(ParseError::TooManyNodes { limit: 3, .. }))
+        ));
+        assert!(matches!(
+            parse_reader_with_limits(io::Read::chain(&b"\""[..], io::repeat(b'a')), limits),
+            Err(Error::Tokenize(TokenizerError::InputTooLarge {
+                limit: 16,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn error_with_source() {
+        let source = "{\n  \"key\": \"value\",\n  \"other\" 1\n}";
+        let err = parse(source).unwrap_err();
+
+        assert_eq!(
+            err.with_source(source).to_string(),
+            "unexpected token 1 at line 3, column 11\n  |\n3 |   \"other\" 1\n  |           ^"
+        );
+    }
+
+    const CORPUS: &[&str] = &[
+        "",
+        " ",
+        "{",
+        "}",
+        "[",
+        "]",
+        ",",
+        ":",
+        "\"",
+        "-",
+        "+",
+        ".",
+        "1.2.3",
+        "--1",
+        "tru",
+        "fals",
+        "nul",
+        "{\"a\" 1}",
+        "{\"a\":}",
+        "{\"a\":1,",
+        "{1: 2}",
+        "{,}",
+        "[1,2",
+        "[,",
+        "[1,]",
+        "[}",
+        "{]",
+        "\"abc",
+        r#"{"key": [1, "two", 3.0, true, null], "nested": {"a": {"b": []}}}"#,
+    ];
+
+    #[test]
+    fn fuzz_corpus_never_panics() {
+        for input in CORPUS {
+            let _ = parse(input);
+
+            // 途中で切った入力もすべて試す
+            for (i, _) in input.char_indices() {
+                let _ = parse(&input[..i]);
+                let _ = parse(&input[i..]);
+            }
+        }
+    }
+
+    #[test]
+    fn fuzz_random_inputs_never_panics() {
+        const ALPHABET: &[char] = &[
+            '{', '}', '[', ']', ',', ':', '"', '\\', '-', '+', '.', '0', '1', '9', 'e', 'E', 't',
+            'r', 'u', 'f', 'a', 'l', 's', 'n', ' ', '\n', 'あ',
+        ];
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..20_000 {
+            let len = rng.below(32);
+            let input: String = (0..len)
+                .map(|_| ALPHABET[rng.below(ALPHABET.len())])
+                .collect();
+
+            let _ = parse(&input);
+        }
+    }
+
+    #[test]
+    fn fuzz_random_bytes_never_panics() {
+        const ALPHABET: &[u8] = b"{}[],:\"\\-.019eturn \x80\xbf\xc3\xe3\xed\xf0\xff";
+
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..20_000 {
+            let len = rng.below(32);
+            let input: Vec<u8> = (0..len)
+                .map(|_| ALPHABET[rng.below(ALPHABET.len())])
+                .collect();
+
+            let _ = parse_slice(&input);
+            let _ = parse_reader(input.as_slice());
+        }
+    }
+
+    #[test]
+    fn parse_bytes_matches_parse() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..200 {
+            let element = rng.element(4);
+            let json = PrettyPrinter::default().print(&element);
+
+            assert_eq!(parse_slice(json.as_bytes()).as_ref(), Ok(&element));
+            assert_eq!(parse_reader(json.as_bytes()).as_ref(), Ok(&element));
+            // 文字の途中で読み込みが分かれても同じ結果になる
+            assert_eq!(
+                parse_reader(Trickle(json.as_bytes())).as_ref(),
+                Ok(&element)
+            );
+        }
+
+        assert_eq!(parse_reader("[1, 2".as_bytes()), parse("[1, 2"));
+        assert_eq!(parse_slice(b"[1, 2"), parse("[1, 2"));
+    }
+}
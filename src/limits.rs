@@ -0,0 +1,60 @@
+/// Upper bounds for untrusted input. `None` means unlimited.
+///
+/// Use `parse_with_limits` or `parse_reader_with_limits` to apply all of
+/// them at once. `TokenizerOptions` and `ParserOptions` each take only the
+/// part they enforce, see `tokenizer()` and `parser()`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Limits {
+    // 入力全体のバイト数
+    pub max_input_bytes: Option<usize>,
+    // デコード後の1つの文字列のバイト数
+    pub max_string_length: Option<usize>,
+    // 1つの数値のテキストのバイト数
+    pub max_number_length: Option<usize>,
+    // 1つのオブジェクトのメンバー数
+    pub max_object_members: Option<usize>,
+    // 1つの配列の要素数
+    pub max_array_elements: Option<usize>,
+    // 文書全体の値の数
+    pub max_nodes: Option<usize>,
+}
+
+impl Limits {
+    pub fn tokenizer(&self) -> TokenizerLimits {
+        TokenizerLimits {
+            max_input_bytes: self.max_input_bytes,
+            max_string_length: self.max_string_length,
+            max_number_length: self.max_number_length,
+        }
+    }
+
+    pub fn parser(&self) -> ParserLimits {
+        ParserLimits {
+            max_object_members: self.max_object_members,
+            max_array_elements: self.max_array_elements,
+            max_nodes: self.max_nodes,
+        }
+    }
+}
+
+/// The sizes the tokenizer enforces. `None` means unlimited.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TokenizerLimits {
+    pub max_input_bytes: Option<usize>,
+    pub max_string_length: Option<usize>,
+    pub max_number_length: Option<usize>,
+}
+
+/// The container sizes and node count the parser enforces. `None` means
+/// unlimited.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParserLimits {
+    pub max_object_members: Option<usize>,
+    pub max_array_elements: Option<usize>,
+    pub max_nodes: Option<usize>,
+}
+
+// countが上限を超えていればその上限を返す
+pub(crate) fn exceeded(limit: Option<usize>, count: usize) -> Option<usize> {
+    limit.filter(|&limit| count > limit)
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+use std::io::{self, BufRead};
+
+use crate::element::Element;
+use crate::parser::{Parser, ParserOptions};
+use crate::tokenizer::{Tokenizer, TokenizerOptions};
+use crate::Error;
+
+/// What `JsonLinesReader` does after a line fails to parse.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum LineErrorPolicy {
+    // エラーを返して読み込みをやめる
+    #[default]
+    Abort,
+    // エラーを返して次の行から読み続ける
+    Skip,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct JsonLinesOptions {
+    pub on_error: LineErrorPolicy,
+    pub tokenizer: TokenizerOptions,
+    pub parser: ParserOptions,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum JsonLinesError {
+    Io { kind: io::ErrorKind, line: usize },
+    // spanは行の先頭からの位置
+    Parse { error: Error, line: usize },
+}
+
+impl JsonLinesError {
+    /// The 1-based line the error occurred on.
+    pub fn line(&self) -> usize {
+        match self {
+            JsonLinesError::Io { line, .. } | JsonLinesError::Parse { line, .. } => *line,
+        }
+    }
+}
+
+impl fmt::Display for JsonLinesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonLinesError::Io { kind, line } => {
+                write!(f, "failed to read line {}: {}", line, kind)
+            }
+            JsonLinesError::Parse { error, line } => {
+                write!(f, "invalid JSON on line {}: {}", line, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for JsonLinesError {}
+
+/// Reads newline-delimited JSON, parsing one element per line. Blank lines
+/// are ignored.
+pub struct JsonLinesReader<R> {
+    reader: R,
+    options: JsonLinesOptions,
+    buf: Vec<u8>,
+    // 最後に読んだ行の番号
+    line: usize,
+    finished: bool,
+}
+
+impl<R: BufRead> JsonLinesReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, JsonLinesOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: JsonLinesOptions) -> Self {
+        JsonLinesReader {
+            reader,
+            options,
+            buf: vec![],
+            line: 0,
+            finished: false,
+        }
+    }
+
+    /// The 1-based number of the last line read, or 0 before the first.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    fn next_element(&mut self) -> Result<Option<Element<'static>>, JsonLinesError> {
+        loop {
+            // 入力の終わりでは行番号を進めない
+            match self.read_line() {
+                Ok(0) => return Ok(None),
+                Ok(_) => self.line += 1,
+                Err(err) => {
+                    self.line += 1;
+                    return Err(JsonLinesError::Io {
+                        kind: err.kind(),
+                        line: self.line,
+                    });
+                }
+            }
+
+            // 行末の \n と \r\n を取り除く
+            let line = self.buf.strip_suffix(b"\n").unwrap_or(&self.buf);
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+
+            // 空行は読み飛ばす (空白はトークナイザーと同じ4文字だけ)
+            if line
+                .iter()
+                .all(|byte| matches!(byte, b' ' | b'\t' | b'\n' | b'\r'))
+            {
+                continue;
+            }
+
+            let tokens = Tokenizer::from_slice_with_options(line, self.options.tokenizer);
+
+            return match Parser::with_options(tokens, self.options.parser).parse() {
+                Ok(element) => Ok(Some(element.into_owned())),
+                Err(err) => Err(JsonLinesError::Parse {
+                    error: err.into(),
+                    line: self.line,
+                }),
+            };
+        }
+    }
+
+    // 1行読んで改行までのバイト数を返す。max_input_bytesを超える分は読み飛ばし、
+    // バッファには上限より1バイトだけ多く残してトークナイザーにエラーを返させる
+    fn read_line(&mut self) -> io::Result<usize> {
+        let capacity = match self.options.tokenizer.limits.max_input_bytes {
+            Some(limit) => limit.saturating_add(1),
+            None => usize::MAX,
+        };
+        let mut read = 0;
+
+        self.buf.clear();
+
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+
+            if available.is_empty() {
+                return Ok(read);
+            }
+
+            let (used, done) = match available.iter().position(|&byte| byte == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (available.len(), false),
+            };
+            let room = capacity - self.buf.len();
+            self.buf.extend_from_slice(&available[..used.min(room)]);
+
+            self.reader.consume(used);
+            read += used;
+
+            if done {
+                return Ok(read);
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLinesReader<R> {
+    type Item = Result<Element<'static>, JsonLinesError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        match self.next_element() {
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Ok(Some(element)) => Some(Ok(element)),
+            Err(err) => {
+                // 読み込みのエラーからは続けられない
+                self.finished = matches!(err, JsonLinesError::Io { .. })
+                    || self.options.on_error == LineErrorPolicy::Abort;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Writes elements as newline-delimited JSON, one compact element per line.
+pub struct JsonLinesWriter<W> {
+    writer: W,
+}
+
+impl<W: io::Write> JsonLinesWriter<W> {
+    pub fn new(writer: W) -> Self {
+        JsonLinesWriter { writer }
+    }
+
+    pub fn write(&mut self, element: &Element) -> io::Result<()> {
+        // 文字列中の改行はエスケープされるので1行に収まる
+        element.to_writer(&mut self.writer)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::ParseError;
+    use crate::test_util::Rng;
+    use crate::tokenizer::TokenizerError;
+
+    #[test]
+    fn write_and_read_lines() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let elements: Vec<Element> = (0..100).map(|_| rng.element(3)).collect();
+
+        let mut writer = JsonLinesWriter::new(vec![]);
+        for element in &elements {
+            writer.write(element).unwrap();
+        }
+        let bytes = writer.into_inner();
+
+        assert_eq!(bytes.iter().filter(|&&byte| byte == b'\n').count(), 100);
+
+        let read: Result<Vec<Element>, JsonLinesError> =
+            JsonLinesReader::new(bytes.as_slice()).collect();
+        assert_eq!(read, Ok(elements));
+
+        // 空行と \r\n は無視する
+        let mut reader = JsonLinesReader::new("1\r\n\n  \n{\"a\": \"b\"}".as_bytes());
+        assert_eq!(reader.next(), Some(Ok(crate::parse("1").unwrap())));
+        assert_eq!(reader.line(), 1);
+        assert_eq!(
+            reader.next(),
+            Some(Ok(crate::parse(r#"{"a": "b"}"#).unwrap()))
+        );
+        assert_eq!(reader.line(), 4);
+        assert_eq!(reader.next(), None);
+        assert_eq!(reader.line(), 4);
+
+        let mut reader = JsonLinesReader::new("1\n2\n".as_bytes());
+        assert_eq!(reader.by_ref().count(), 2);
+        assert_eq!(reader.line(), 2);
+
+        // 改ページだけの行は空行ではない
+        let mut reader = JsonLinesReader::new("1\n\x0c\n".as_bytes());
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(JsonLinesError::Parse {
+                error: Error::Tokenize(TokenizerError::InvalidCharacter {
+                    character: '\x0c',
+                    ..
+                }),
+                line: 2
+            }))
+        ));
+    }
+
+    #[test]
+    fn line_errors() {
+        let input: &[u8] = b"1\n[1,\n{} {}\n\"\xff\"\n4\n";
+
+        let errors: Vec<(usize, Error)> = JsonLinesReader::with_options(
+            input,
+            JsonLinesOptions {
+                on_error: LineErrorPolicy::Skip,
+                ..JsonLinesOptions::default()
+            },
+        )
+        .filter_map(Result::err)
+        .map(|err| match err {
+            JsonLinesError::Parse { error, line } => (line, error),
+            err => panic!("unexpected error {:?}", err),
+        })
+        .collect();
+
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            errors[0],
+            (2, Error::Parse(ParseError::UnexpectedEof { .. }))
+        ));
+        assert!(matches!(
+            errors[1],
+            (3, Error::Parse(ParseError::TrailingCharacters { .. }))
+        ));
+        assert!(matches!(
+            errors[2],
+            (4, Error::Tokenize(TokenizerError::InvalidUtf8 { .. }))
+        ));
+
+        // 長すぎる行は上限を少し超えたところまでしか読み込まない
+        let limits = crate::limits::TokenizerLimits {
+            max_input_bytes: Some(16),
+            ..Default::default()
+        };
+        let long = io::Read::chain(&b"1\n\"abc"[..], io::Read::take(io::repeat(b'a'), 1 << 20));
+        let long_input = io::BufReader::new(io::Read::chain(long, &b"\"\n[2]\n"[..]));
+        let mut reader = JsonLinesReader::with_options(
+            long_input,
+            JsonLinesOptions {
+                on_error: LineErrorPolicy::Skip,
+                tokenizer: crate::tokenizer::TokenizerOptions {
+                    limits,
+                    ..Default::default()
+                },
+                ..JsonLinesOptions::default()
+            },
+        );
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        assert!(matches!(
+            reader.next(),
+            Some(Err(JsonLinesError::Parse {
+                error: Error::Tokenize(TokenizerError::InputTooLarge { limit: 16, .. }),
+                line: 2
+            }))
+        ));
+        assert!(reader.buf.capacity() < 64);
+        assert_eq!(reader.next(), Some(Ok(crate::parse("[2]").unwrap())));
+        assert_eq!(reader.line(), 3);
+
+        // 既定では最初のエラーで止まる
+        let mut reader = JsonLinesReader::new(input);
+        assert!(matches!(reader.next(), Some(Ok(_))));
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.line(), 2);
+        assert!(err.to_string().starts_with("invalid JSON on line 2: "));
+        assert_eq!(reader.next(), None);
+    }
+}
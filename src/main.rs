@@ -1,27 +1,32 @@
-use crate::{
-    parser::Parser,
-    tokenizer::{Tokenizer, TokenizerTrait},
+use std::{
+    env, fs,
+    io::{self, Read},
+    process,
 };
 
-mod parser;
-mod tokenizer;
-
 fn main() {
-    let mut tokenizer = Tokenizer::new(
-        r#"
-        {
-          "key": "value",
+    // 引数があればファイルから、なければ標準入力から読み込む
+    let input = match env::args().nth(1) {
+        Some(path) => fs::read_to_string(path),
+        None => {
+            let mut input = String::new();
+            io::stdin().read_to_string(&mut input).map(|_| input)
         }
-    "#,
-    );
-
-    let result = tokenizer.tokenize();
-
-    println!("{:?}", result.clone().unwrap());
+    };
 
-    let mut parser = Parser::new(result.unwrap());
-
-    let result = parser.parse();
+    let input = match input {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
 
-    println!("{:?}", result.unwrap());
+    match json_parser::parse(&input) {
+        Ok(element) => println!("{}", element),
+        Err(err) => {
+            eprintln!("{}", err.with_source(&input));
+            process::exit(1);
+        }
+    }
 }
@@ -0,0 +1,224 @@
+use std::fmt;
+
+/// A JSON number that keeps integers exact.
+///
+/// Integers that fit in `u64`/`i64` are stored as such, everything else as
+/// `f64`. In arbitrary precision mode the original text is kept as-is.
+#[derive(PartialEq, Clone)]
+pub struct Number {
+    n: N,
+}
+
+#[derive(PartialEq, Clone)]
+enum N {
+    PosInt(u64),
+    // 常に負の値
+    NegInt(i64),
+    Float(f64),
+    Raw(String),
+}
+
+impl Number {
+    // 文法は検証済みのテキストを受け取る
+    pub(crate) fn from_text(text: &str, arbitrary_precision: bool) -> Option<Number> {
+        if arbitrary_precision {
+            return Some(Number {
+                n: N::Raw(normalize(text)),
+            });
+        }
+
+        if !text.contains(['.', 'e', 'E']) {
+            if text.starts_with('-') {
+                // -0 は符号を残すためにf64にする
+                if let Ok(val @ (..=-1)) = text.parse::<i64>() {
+                    return Some(Number::from(val));
+                }
+            } else if let Ok(val) = text.parse::<u64>() {
+                return Some(Number::from(val));
+            }
+        }
+
+        match text.parse::<f64>() {
+            Ok(val) if val.is_finite() => Some(Number { n: N::Float(val) }),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match &self.n {
+            N::PosInt(val) => i64::try_from(*val).ok(),
+            N::NegInt(val) => Some(*val),
+            N::Float(_) => None,
+            N::Raw(text) => text.parse().ok(),
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match &self.n {
+            N::PosInt(val) => Some(*val),
+            N::NegInt(_) | N::Float(_) => None,
+            N::Raw(text) => text.parse().ok(),
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match &self.n {
+            N::PosInt(val) => Some(*val as f64),
+            N::NegInt(val) => Some(*val as f64),
+            N::Float(val) => Some(*val),
+            N::Raw(text) => text.parse().ok().filter(|val: &f64| val.is_finite()),
+        }
+    }
+
+    /// The original text of the number, only available in arbitrary
+    /// precision mode.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.n {
+            N::Raw(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+// 緩い表記の `+1` や `.5` をJSONとして正しい `1` や `0.5` にする
+fn normalize(text: &str) -> String {
+    let text = text.strip_prefix('+').unwrap_or(text);
+
+    match text.strip_prefix('-').unwrap_or(text).strip_prefix('.') {
+        Some(frac) if text.starts_with('-') => format!("-0.{}", frac),
+        Some(frac) => format!("0.{}", frac),
+        None => text.to_string(),
+    }
+}
+
+macro_rules! from_unsigned {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(val: $ty) -> Self {
+                    Number { n: N::PosInt(val as u64) }
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! from_signed {
+    ($($ty:ty)*) => {
+        $(
+            impl From<$ty> for Number {
+                fn from(val: $ty) -> Self {
+                    let n = if val < 0 {
+                        N::NegInt(val as i64)
+                    } else {
+                        N::PosInt(val as u64)
+                    };
+
+                    Number { n }
+                }
+            }
+        )*
+    };
+}
+
+from_unsigned!(u8 u16 u32 u64 usize);
+from_signed!(i8 i16 i32 i64 isize);
+
+impl From<f64> for Number {
+    fn from(val: f64) -> Self {
+        Number { n: N::Float(val) }
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.n {
+            N::PosInt(val) => write!(f, "{}", val),
+            N::NegInt(val) => write!(f, "{}", val),
+            // Debug表記は小数点や指数を残すので読み直しても浮動小数点数になる
+            N::Float(val) => write!(f, "{:?}", val),
+            N::Raw(text) => write!(f, "{}", text),
+        }
+    }
+}
+
+impl fmt::Debug for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.n {
+            N::Raw(text) => write!(f, "Number({:?})", text),
+            _ => write!(f, "Number({})", self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integers_are_exact() {
+        let num = Number::from_text("9007199254740993", false).unwrap();
+        assert_eq!(num.as_u64(), Some(9_007_199_254_740_993));
+        assert_eq!(num.as_i64(), Some(9_007_199_254_740_993));
+
+        let num = Number::from_text("18446744073709551615", false).unwrap();
+        assert_eq!(num.as_u64(), Some(u64::MAX));
+        assert_eq!(num.as_i64(), None);
+
+        let num = Number::from_text("-9223372036854775808", false).unwrap();
+        assert_eq!(num.as_i64(), Some(i64::MIN));
+        assert_eq!(num.as_u64(), None);
+
+        assert_eq!(Number::from_text("1", false), Some(Number::from(1)));
+        assert_eq!(Number::from_text("-1", false), Some(Number::from(-1)));
+        assert_eq!(Number::from(1i64), Number::from(1u64));
+
+        let num = Number::from_text("-0", false).unwrap();
+        assert_eq!(num.as_f64().map(f64::is_sign_negative), Some(true));
+        assert_eq!(num.as_u64(), None);
+        assert_eq!(num.to_string(), "-0.0");
+    }
+
+    #[test]
+    fn floats() {
+        let num = Number::from_text("1.5", false).unwrap();
+        assert_eq!(num.as_f64(), Some(1.5));
+        assert_eq!(num.as_i64(), None);
+        assert_eq!(num.as_str(), None);
+
+        // u64に収まらない整数はf64になる
+        let num = Number::from_text("18446744073709551616", false).unwrap();
+        assert_eq!(num.as_u64(), None);
+        assert_eq!(num.as_f64(), Some(18446744073709551616.0));
+
+        assert_eq!(Number::from_text("1e999", false), None);
+    }
+
+    #[test]
+    fn arbitrary_precision() {
+        let text = "123456789012345678901234567890.123456789";
+        let num = Number::from_text(text, true).unwrap();
+        assert_eq!(num.as_str(), Some(text));
+        assert_eq!(num.to_string(), text);
+        assert_eq!(num.as_u64(), None);
+
+        assert_eq!(Number::from_text("+1", true).unwrap().to_string(), "1");
+        assert_eq!(Number::from_text(".5", true).unwrap().to_string(), "0.5");
+        assert_eq!(
+            Number::from_text("-.5e1", true).unwrap().to_string(),
+            "-0.5e1"
+        );
+
+        let num = Number::from_text("42", true).unwrap();
+        assert_eq!(num.as_u64(), Some(42));
+        assert_eq!(num.as_f64(), Some(42.0));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Number::from(1).to_string(), "1");
+        assert_eq!(Number::from(-1).to_string(), "-1");
+        assert_eq!(Number::from(1.0).to_string(), "1.0");
+        assert_eq!(Number::from(2.5e-8).to_string(), "2.5e-8");
+    }
+}
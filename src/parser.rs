@@ -17,167 +17,676 @@
 
 // null = 'null'
 
+use std::borrow::Cow;
+use std::fmt;
 use std::iter::Peekable;
-use std::vec::IntoIter;
 
-use crate::tokenizer::Token;
+pub use crate::element::{Array, Element, Member, Object};
+use crate::handler::JsonHandler;
+use crate::limits::{exceeded, ParserLimits};
+use crate::number::Number;
+use crate::span::Span;
+use crate::stream::Documents;
+use crate::tokenizer::{Token, TokenizerError};
 
-#[derive(Debug, PartialEq, Clone)]
-struct Member {
-    key: String,
-    value: Element,
+/// What to do when an object contains the same key more than once.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DuplicateKeyPolicy {
+    // ParseError::DuplicateKey を返す
+    Error,
+    // 最初の値を残す
+    FirstWins,
+    // 最初の位置に最後の値を残す
+    LastWins,
+    // すべてのメンバーを残す
+    #[default]
+    KeepAll,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Object {
-    members: Vec<Member>,
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ParserOptions {
+    // `[1, 2,]` のような末尾のカンマを許可する
+    pub allow_trailing_commas: bool,
+    // `[1 2]` のようなカンマの省略を許可する
+    pub allow_missing_commas: bool,
+    pub duplicate_keys: DuplicateKeyPolicy,
+    // オブジェクトと配列の入れ子の上限 (再帰によるスタックオーバーフローを防ぐ)
+    pub max_depth: usize,
+    pub limits: ParserLimits,
 }
 
-#[derive(Debug, PartialEq, Clone)]
-pub struct Array {
-    elements: Vec<Element>,
+impl Default for ParserOptions {
+    fn default() -> Self {
+        ParserOptions {
+            allow_trailing_commas: false,
+            allow_missing_commas: false,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            max_depth: 128,
+            limits: ParserLimits::default(),
+        }
+    }
+}
+
+/// Parses JSON from any iterator of tokens, such as a `Tokenizer`, pulling
+/// tokens only as they are needed.
+pub struct Parser<I: Iterator> {
+    tokens: Peekable<I>,
+    options: ParserOptions,
+    // 現在のオブジェクトと配列の入れ子の深さ
+    depth: usize,
+    // これまでに読んだ値の数
+    nodes: usize,
+    // 最後に読んだトークンの位置 (トークンが尽きたときのエラー用)
+    last_span: Span,
 }
 
 #[derive(Debug, PartialEq, Clone)]
-pub enum Element {
-    String(String),
-    Number(f64),
-    Boolean(bool),
-    Null,
-    Object(Object),
-    Array(Array),
+pub enum ParseError {
+    // トークン列の途中で起きたトークナイザーのエラー
+    Tokenize(TokenizerError),
+    UnexpectedToken {
+        token: Token<'static>,
+        span: Span,
+    },
+    UnexpectedEof {
+        span: Span,
+    },
+    TrailingCharacters {
+        span: Span,
+    },
+    DuplicateKey {
+        key: String,
+        first: Span,
+        second: Span,
+    },
+    DepthLimitExceeded {
+        depth: usize,
+        span: Span,
+    },
+    TooManyMembers {
+        limit: usize,
+        span: Span,
+    },
+    TooManyElements {
+        limit: usize,
+        span: Span,
+    },
+    TooManyNodes {
+        limit: usize,
+        span: Span,
+    },
+    // 区切りの直前で終わっている数値やリテラルは途中で切れた可能性がある
+    TruncatedRecord {
+        span: Span,
+    },
 }
 
-pub struct Parser {
-    tokens: Peekable<IntoIter<Token>>,
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::Tokenize(err) => err.span(),
+            ParseError::UnexpectedToken { span, .. }
+            | ParseError::UnexpectedEof { span }
+            | ParseError::TrailingCharacters { span }
+            | ParseError::DepthLimitExceeded { span, .. }
+            | ParseError::TooManyMembers { span, .. }
+            | ParseError::TooManyElements { span, .. }
+            | ParseError::TooManyNodes { span, .. }
+            | ParseError::TruncatedRecord { span } => *span,
+            ParseError::DuplicateKey { second, .. } => *second,
+        }
+    }
 }
 
-#[derive(Debug)]
-pub enum ParseError {
-    UnexpectedToken(Token),
-    UnexpectedEof,
-    ParseError,
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Tokenize(err) => err.fmt(f),
+            ParseError::UnexpectedToken { token, span } => {
+                write!(f, "unexpected token {} at {}", token, span)
+            }
+            ParseError::UnexpectedEof { span } => {
+                write!(f, "unexpected end of input at {}", span)
+            }
+            ParseError::TrailingCharacters { span } => {
+                write!(f, "trailing characters at {}", span)
+            }
+            ParseError::DuplicateKey { key, first, second } => {
+                write!(
+                    f,
+                    "duplicate key {:?} at {} (first defined at {})",
+                    key, second, first
+                )
+            }
+            ParseError::DepthLimitExceeded { depth, span } => {
+                write!(f, "nesting depth {} exceeds the limit at {}", depth, span)
+            }
+            ParseError::TooManyMembers { limit, span } => {
+                write!(f, "object has more than {} members at {}", limit, span)
+            }
+            ParseError::TooManyElements { limit, span } => {
+                write!(f, "array has more than {} elements at {}", limit, span)
+            }
+            ParseError::TooManyNodes { limit, span } => {
+                write!(f, "document has more than {} values at {}", limit, span)
+            }
+            ParseError::TruncatedRecord { span } => {
+                write!(f, "record may be truncated at {}", span)
+            }
+        }
+    }
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        let tokens = tokens.into_iter().peekable();
+impl std::error::Error for ParseError {}
 
-        Self { tokens }
+impl From<TokenizerError> for ParseError {
+    fn from(err: TokenizerError) -> Self {
+        ParseError::Tokenize(err)
     }
+}
 
-    pub fn parse(&mut self) -> Result<Element, ParseError> {
-        let node = self.parse_element().expect("parse element error");
+impl<'a, I> Parser<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    pub fn new(tokens: I) -> Self {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    pub fn with_options(tokens: I, options: ParserOptions) -> Self {
+        let tokens = tokens.peekable();
+
+        Self {
+            tokens,
+            options,
+            depth: 0,
+            nodes: 0,
+            last_span: Span::default(),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Element<'a>, ParseError> {
+        let node = self.parse_element()?;
+
+        self.parse_end()?;
 
         Ok(node)
     }
-    fn parse_element(&mut self) -> Result<Element, ParseError> {
-        let node = match self.tokens.next() {
-            Some(token) => match token {
-                Token::String(val) => Element::String(val),
-                Token::Number(val) => Element::Number(val),
-                Token::Bool(val) => Element::Boolean(val),
-                Token::Null => Element::Null,
-                Token::LeftBrace => self.parse_object().expect("parse object error"),
-                Token::LeftBracket => self.parse_array().expect("parse array error"),
-                _ => return Err(ParseError::UnexpectedToken(token.clone())),
+
+    /// Parses a single value, reporting it to `handler` piece by piece
+    /// instead of building an `Element`.
+    pub fn parse_with<H: JsonHandler<'a>>(&mut self, handler: &mut H) -> Result<(), H::Error> {
+        self.parse_value(handler)?;
+
+        self.parse_end()?;
+
+        Ok(())
+    }
+
+    /// Parses every top-level value in the input, e.g. `{} {}` or `1 2 3`.
+    /// As with `into_documents`, the limits count each value separately.
+    pub fn parse_all(&mut self) -> Result<Vec<Element<'a>>, ParseError> {
+        let mut nodes = vec![];
+
+        while !self.at_end() {
+            self.reset_nodes();
+            nodes.push(self.parse_element()?);
+        }
+
+        Ok(nodes)
+    }
+
+    /// Reads concatenated documents such as `{} {}` or `{}{}` one at a time,
+    /// yielding each with the byte offset it starts at.
+    pub fn into_documents(self) -> Documents<I> {
+        Documents::new(self)
+    }
+
+    pub(crate) fn at_end(&mut self) -> bool {
+        matches!(self.tokens.peek(), None | Some(Ok((Token::End, _))))
+    }
+
+    // ルート要素の後ろに何も残っていないことを確認する
+    pub(crate) fn parse_end(&mut self) -> Result<(), ParseError> {
+        match self.tokens.next() {
+            None | Some(Ok((Token::End, _))) => Ok(()),
+            Some(Ok((_, span))) => Err(ParseError::TrailingCharacters { span }),
+            Some(Err(err)) => Err(err.into()),
+        }
+    }
+
+    pub(crate) fn next_token(&mut self) -> Result<(Token<'a>, Span), ParseError> {
+        match self.tokens.next() {
+            Some(Ok((token, span))) => {
+                self.last_span = span;
+                Ok((token, span))
+            }
+            Some(Err(err)) => Err(err.into()),
+            None => Err(self.eof()),
+        }
+    }
+
+    pub(crate) fn peek_token(&mut self) -> Result<&Token<'a>, ParseError> {
+        let eof = self.eof();
+
+        match self.tokens.peek() {
+            Some(Ok((token, _))) => Ok(token),
+            Some(Err(err)) => Err(err.clone().into()),
+            None => Err(eof),
+        }
+    }
+
+    pub(crate) fn peek_span(&mut self) -> Span {
+        match self.tokens.peek() {
+            Some(Ok((_, span))) => *span,
+            Some(Err(err)) => err.span(),
+            None => self.eof().span(),
+        }
+    }
+
+    // 次の文書の値を数え直す
+    pub(crate) fn reset_nodes(&mut self) {
+        self.nodes = 0;
+    }
+
+    // 最後に読んだトークンの位置
+    pub(crate) fn last_span(&self) -> Span {
+        self.last_span
+    }
+
+    fn eof(&self) -> ParseError {
+        ParseError::UnexpectedEof {
+            span: Span {
+                start: self.last_span.end,
+                ..self.last_span
             },
-            _ => return Err(ParseError::ParseError),
-        };
+        }
+    }
 
-        Ok(node)
+    // 値を1つ読んでElementを組み立てる
+    pub(crate) fn parse_element(&mut self) -> Result<Element<'a>, ParseError> {
+        let mut builder = ElementBuilder::new(self.options.duplicate_keys);
+
+        self.parse_value(&mut builder)?;
+
+        Ok(builder.finish())
     }
 
-    fn parse_object(&mut self) -> Result<Element, ParseError> {
-        let members = self.parse_members().expect("parse members error in object");
+    fn parse_value<H: JsonHandler<'a>>(&mut self, handler: &mut H) -> Result<(), H::Error> {
+        let (token, span) = self.next_token()?;
 
-        // println!("{:?}", members);
+        self.count_node(span)?;
 
-        let node = Element::Object(Object { members });
+        match token {
+            Token::String(val) => handler.string(val, span),
+            Token::Number(val) => handler.number(val, span),
+            Token::Bool(val) => handler.bool(val, span),
+            Token::Null => handler.null(span),
+            Token::LeftBrace => self.nested(handler, span, Self::parse_object),
+            Token::LeftBracket => self.nested(handler, span, Self::parse_array),
+            token => Err(unexpected(token, span).into()),
+        }
+    }
 
-        if let Token::RightBrace = self.tokens.next().expect("next token error") {
-            return Ok(node);
-        };
+    // 入れ子を1段深くしてオブジェクトか配列を読む
+    fn nested<H: JsonHandler<'a>>(
+        &mut self,
+        handler: &mut H,
+        span: Span,
+        parse: fn(&mut Self, &mut H, Span) -> Result<(), H::Error>,
+    ) -> Result<(), H::Error> {
+        self.enter(span)?;
+        let result = parse(self, handler, span);
+        self.leave();
+
+        result
+    }
+
+    // 入れ子を1段深くする
+    pub(crate) fn enter(&mut self, span: Span) -> Result<(), ParseError> {
+        if self.depth >= self.options.max_depth {
+            return Err(ParseError::DepthLimitExceeded {
+                depth: self.depth + 1,
+                span,
+            });
+        }
 
-        Err(ParseError::ParseError)
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    pub(crate) fn leave(&mut self) {
+        self.depth -= 1;
     }
 
-    fn parse_members(&mut self) -> Result<Vec<Member>, ParseError> {
-        let mut members: Vec<Member> = vec![];
+    pub(crate) fn count_node(&mut self, span: Span) -> Result<(), ParseError> {
+        self.nodes += 1;
+
+        match exceeded(self.options.limits.max_nodes, self.nodes) {
+            Some(limit) => Err(ParseError::TooManyNodes { limit, span }),
+            None => Ok(()),
+        }
+    }
+
+    // countはkey_spanのメンバーが何番目か
+    pub(crate) fn count_member(&self, count: usize, key_span: Span) -> Result<(), ParseError> {
+        match exceeded(self.options.limits.max_object_members, count) {
+            Some(limit) => Err(ParseError::TooManyMembers {
+                limit,
+                span: key_span,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // countは次の要素が何番目か
+    pub(crate) fn count_element(&mut self, count: usize) -> Result<(), ParseError> {
+        match exceeded(self.options.limits.max_array_elements, count) {
+            Some(limit) => Err(ParseError::TooManyElements {
+                limit,
+                span: self.peek_span(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn parse_object<H: JsonHandler<'a>>(
+        &mut self,
+        handler: &mut H,
+        span: Span,
+    ) -> Result<(), H::Error> {
+        handler.start_object(span)?;
+
+        self.parse_members(handler)?;
+
+        match self.next_token()? {
+            (Token::RightBrace, span) => handler.end_object(span),
+            (token, span) => Err(unexpected(token, span).into()),
+        }
+    }
 
-        while let Some(tok) = self.tokens.peek().cloned() {
-            // Commaだったら進める
-            if let Token::Comma = tok {
-                self.tokens.next();
+    fn parse_members<H: JsonHandler<'a>>(&mut self, handler: &mut H) -> Result<(), H::Error> {
+        if let Token::RightBrace = self.peek_token()? {
+            return Ok(());
+        }
+
+        let mut count = 0;
+
+        loop {
+            count += 1;
+            self.parse_member(handler, count)?;
+
+            if self.parse_separator(Token::RightBrace)? {
+                break;
             }
-            if let Token::RightBrace = self.tokens.peek().unwrap() {
+        }
+
+        Ok(())
+    }
+
+    // countはこのメンバーが何番目か
+    fn parse_member<H: JsonHandler<'a>>(
+        &mut self,
+        handler: &mut H,
+        count: usize,
+    ) -> Result<(), H::Error> {
+        let (key, key_span) = match self.next_token()? {
+            (Token::String(key), span) => (key, span),
+            (token, span) => return Err(unexpected(token, span).into()),
+        };
+
+        self.count_member(count, key_span)?;
+
+        match self.next_token()? {
+            (Token::Colon, _) => {}
+            (token, span) => return Err(unexpected(token, span).into()),
+        }
+
+        handler.key(key, key_span)?;
+
+        self.parse_value(handler)
+    }
+
+    fn parse_array<H: JsonHandler<'a>>(
+        &mut self,
+        handler: &mut H,
+        span: Span,
+    ) -> Result<(), H::Error> {
+        handler.start_array(span)?;
+
+        self.parse_elements(handler)?;
+
+        match self.next_token()? {
+            (Token::RightBracket, span) => handler.end_array(span),
+            (token, span) => Err(unexpected(token, span).into()),
+        }
+    }
+
+    fn parse_elements<H: JsonHandler<'a>>(&mut self, handler: &mut H) -> Result<(), H::Error> {
+        if let Token::RightBracket = self.peek_token()? {
+            return Ok(());
+        }
+
+        let mut count = 0;
+
+        loop {
+            count += 1;
+            self.count_element(count)?;
+
+            self.parse_value(handler)?;
+
+            if self.parse_separator(Token::RightBracket)? {
                 break;
             }
-            let member = self.parse_member().expect("parse member error in members");
-            members.push(member);
         }
 
-        Ok(members)
+        Ok(())
     }
 
-    fn parse_member(&mut self) -> Result<Member, ParseError> {
-        match self.tokens.next() {
-            Some(Token::String(key)) => match self.tokens.next() {
-                Some(Token::Colon) => {
-                    let value = self.parse_element().expect("parse element error in member");
-                    Ok(Member { key, value })
+    // 要素の後ろのカンマを読む。閉じ括弧に達した場合はtrueを返す
+    pub(crate) fn parse_separator(&mut self, close: Token<'a>) -> Result<bool, ParseError> {
+        let options = self.options;
+
+        match self.peek_token()? {
+            Token::Comma => {
+                self.next_token()?;
+
+                if options.allow_trailing_commas && *self.peek_token()? == close {
+                    return Ok(true);
                 }
-                _ => Err(ParseError::ParseError),
-            },
-            _ => Err(ParseError::ParseError),
+
+                Ok(false)
+            }
+            token if *token == close => Ok(true),
+            _ if options.allow_missing_commas => Ok(false),
+            _ => {
+                let (token, span) = self.next_token()?;
+                Err(unexpected(token, span))
+            }
         }
     }
+}
 
-    fn parse_array(&mut self) -> Result<Element, ParseError> {
-        let elements: Vec<Element> = self.parse_elements().expect("parse elements error");
+// 入力の終端に達した場合はUnexpectedEofとして扱う
+pub(crate) fn unexpected(token: Token, span: Span) -> ParseError {
+    match token {
+        Token::End => ParseError::UnexpectedEof { span },
+        token => ParseError::UnexpectedToken {
+            token: token.into_owned(),
+            span,
+        },
+    }
+}
 
-        let node = Element::Array(Array { elements });
+// 組み立て途中のオブジェクトか配列
+enum Frame<'a> {
+    Object {
+        object: Object<'a>,
+        // objectのメンバーと同じ順番のキーの位置
+        key_spans: Vec<Span>,
+        // 値を待っているキー
+        key: Option<(Cow<'a, str>, Span)>,
+    },
+    Array(Vec<Element<'a>>),
+}
 
-        if let Token::RightBracket = self.tokens.next().expect("next token error") {
-            return Ok(node);
-        };
+// イベントからElementを組み立てるハンドラー
+struct ElementBuilder<'a> {
+    duplicate_keys: DuplicateKeyPolicy,
+    stack: Vec<Frame<'a>>,
+    root: Option<Element<'a>>,
+}
 
-        Err(ParseError::ParseError)
+impl<'a> ElementBuilder<'a> {
+    fn new(duplicate_keys: DuplicateKeyPolicy) -> Self {
+        ElementBuilder {
+            duplicate_keys,
+            stack: vec![],
+            root: None,
+        }
     }
 
-    fn parse_elements(&mut self) -> Result<Vec<Element>, ParseError> {
-        let mut elements: Vec<Element> = vec![];
+    // 値を1つ読み終えていれば必ずrootがある
+    fn finish(self) -> Element<'a> {
+        self.root.unwrap_or(Element::Null)
+    }
 
-        while let Some(tok) = self.tokens.peek().cloned() {
-            // Commaだったら進める
-            if let Token::Comma = tok {
-                self.tokens.next();
+    // 読み終えた値を親のコンテナに入れる
+    fn value(&mut self, value: Element<'a>) -> Result<(), ParseError> {
+        match self.stack.last_mut() {
+            Some(Frame::Object {
+                object,
+                key_spans,
+                key,
+            }) => {
+                if let Some((key, key_span)) = key.take() {
+                    let member = Member { key, value };
+                    add_member(self.duplicate_keys, object, key_spans, member, key_span)?;
+                }
             }
-            if let Token::RightBracket = self.tokens.peek().unwrap() {
-                break;
+            Some(Frame::Array(elements)) => elements.push(value),
+            None => self.root = Some(value),
+        }
+
+        Ok(())
+    }
+}
+
+fn add_member<'a>(
+    duplicate_keys: DuplicateKeyPolicy,
+    object: &mut Object<'a>,
+    key_spans: &mut Vec<Span>,
+    member: Member<'a>,
+    key_span: Span,
+) -> Result<(), ParseError> {
+    if let Some(i) = object.position(&member.key) {
+        match duplicate_keys {
+            DuplicateKeyPolicy::Error => {
+                return Err(ParseError::DuplicateKey {
+                    key: member.key.into_owned(),
+                    first: key_spans[i],
+                    second: key_span,
+                })
             }
-            let element = self
-                .parse_element()
-                .expect("parse element error in parse_elements");
-            elements.push(element);
+            DuplicateKeyPolicy::FirstWins => return Ok(()),
+            DuplicateKeyPolicy::LastWins => {
+                object.insert(member.key, member.value);
+                return Ok(());
+            }
+            DuplicateKeyPolicy::KeepAll => {}
+        }
+    }
+
+    object.push(member);
+    key_spans.push(key_span);
+
+    Ok(())
+}
+
+impl<'a> JsonHandler<'a> for ElementBuilder<'a> {
+    type Error = ParseError;
+
+    fn start_object(&mut self, _span: Span) -> Result<(), ParseError> {
+        self.stack.push(Frame::Object {
+            object: Object::new(),
+            key_spans: vec![],
+            key: None,
+        });
+
+        Ok(())
+    }
+
+    fn key(&mut self, key: Cow<'a, str>, span: Span) -> Result<(), ParseError> {
+        if let Some(Frame::Object { key: pending, .. }) = self.stack.last_mut() {
+            *pending = Some((key, span));
+        }
+
+        Ok(())
+    }
+
+    fn end_object(&mut self, _span: Span) -> Result<(), ParseError> {
+        match self.stack.pop() {
+            Some(Frame::Object { object, .. }) => self.value(Element::Object(object)),
+            _ => Ok(()),
+        }
+    }
+
+    fn start_array(&mut self, _span: Span) -> Result<(), ParseError> {
+        self.stack.push(Frame::Array(vec![]));
+
+        Ok(())
+    }
+
+    fn end_array(&mut self, _span: Span) -> Result<(), ParseError> {
+        match self.stack.pop() {
+            Some(Frame::Array(elements)) => self.value(Element::Array(Array { elements })),
+            _ => Ok(()),
         }
+    }
+
+    fn string(&mut self, value: Cow<'a, str>, _span: Span) -> Result<(), ParseError> {
+        self.value(Element::String(value))
+    }
+
+    fn number(&mut self, value: Number, _span: Span) -> Result<(), ParseError> {
+        self.value(Element::Number(value))
+    }
 
-        Ok(elements)
+    fn bool(&mut self, value: bool, _span: Span) -> Result<(), ParseError> {
+        self.value(Element::Boolean(value))
+    }
+
+    fn null(&mut self, _span: Span) -> Result<(), ParseError> {
+        self.value(Element::Null)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::number::Number;
     use crate::tokenizer::TokenizerTrait;
 
     use super::*;
 
-    fn get_parser(str: String) -> Parser {
+    type Tokens = std::vec::IntoIter<Result<(Token<'static>, Span), TokenizerError>>;
+
+    fn get_parser(str: String) -> Parser<Tokens> {
+        get_parser_with_options(str, ParserOptions::default())
+    }
+
+    fn get_parser_with_options(str: String, options: ParserOptions) -> Parser<Tokens> {
         let mut tokenizer = crate::tokenizer::Tokenizer::new(str.as_str());
 
-        let tokens = tokenizer.tokenize().unwrap();
+        // 入力を借用しないトークンにしておく
+        let tokens: Vec<_> = tokenizer
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|(token, span)| Ok((token.into_owned(), span)))
+            .collect();
 
-        Parser::new(tokens)
+        Parser::with_options(tokens.into_iter(), options)
     }
 
     #[test]
@@ -188,7 +697,7 @@ mod test {
 
         println!("{:?}", result);
 
-        assert_eq!(result, Element::String("これは文字列".to_string()),);
+        assert_eq!(result, Element::String("これは文字列".into()),);
     }
 
     #[test]
@@ -202,7 +711,7 @@ mod test {
 
         let result = parser.parse().unwrap();
 
-        assert_eq!(result, Element::Object(Object { members: vec![] }));
+        assert_eq!(result, Element::Object(Object::from_members(vec![])));
 
         let mut parser = get_parser(
             r#"
@@ -217,16 +726,14 @@ mod test {
         //
         assert_eq!(
             result,
-            Element::Object(Object {
-                members: vec![Member {
-                    key: "key".to_string(),
-                    value: Element::String("value".to_string()),
-                }]
-            })
+            Element::Object(Object::from_members(vec![Member {
+                key: "key".into(),
+                value: Element::String("value".into()),
+            }]))
         );
 
         // けつカンマあり
-        let mut parser = get_parser(
+        let mut parser = get_parser_with_options(
             r#"
       {
         "key": "value",
@@ -236,29 +743,29 @@ mod test {
       }
       "#
             .to_string(),
+            ParserOptions {
+                allow_trailing_commas: true,
+                ..ParserOptions::default()
+            },
         );
 
         let result = parser.parse().unwrap();
         println!("{:?}", result);
         assert_eq!(
             result,
-            Element::Object(Object {
-                members: vec![
-                    Member {
-                        key: "key".to_string(),
-                        value: Element::String("value".to_string()),
-                    },
-                    Member {
-                        key: "key2".to_string(),
-                        value: Element::Object(Object {
-                            members: vec![Member {
-                                key: "key3".to_string(),
-                                value: Element::String("value3".to_string()),
-                            }]
-                        }),
-                    }
-                ]
-            })
+            Element::Object(Object::from_members(vec![
+                Member {
+                    key: "key".into(),
+                    value: Element::String("value".into()),
+                },
+                Member {
+                    key: "key2".into(),
+                    value: Element::Object(Object::from_members(vec![Member {
+                        key: "key3".into(),
+                        value: Element::String("value3".into()),
+                    }])),
+                }
+            ]))
         );
 
         // けつカンマなし
@@ -267,7 +774,7 @@ mod test {
       {
         "key": "value",
         "key2": {
-          "key3": "value3",
+          "key3": "value3"
         }
       }
       "#
@@ -278,23 +785,19 @@ mod test {
         println!("{:?}", result);
         assert_eq!(
             result,
-            Element::Object(Object {
-                members: vec![
-                    Member {
-                        key: "key".to_string(),
-                        value: Element::String("value".to_string()),
-                    },
-                    Member {
-                        key: "key2".to_string(),
-                        value: Element::Object(Object {
-                            members: vec![Member {
-                                key: "key3".to_string(),
-                                value: Element::String("value3".to_string()),
-                            }]
-                        }),
-                    }
-                ]
-            })
+            Element::Object(Object::from_members(vec![
+                Member {
+                    key: "key".into(),
+                    value: Element::String("value".into()),
+                },
+                Member {
+                    key: "key2".into(),
+                    value: Element::Object(Object::from_members(vec![Member {
+                        key: "key3".into(),
+                        value: Element::String("value3".into()),
+                    }])),
+                }
+            ]))
         );
     }
 
@@ -313,9 +816,9 @@ mod test {
             result,
             Element::Array(Array {
                 elements: vec![
-                    Element::Number(1.0),
-                    Element::Number(2.0),
-                    Element::Number(3.0)
+                    Element::Number(Number::from(1)),
+                    Element::Number(Number::from(2)),
+                    Element::Number(Number::from(3))
                 ]
             })
         );
@@ -333,8 +836,8 @@ mod test {
             result,
             Element::Array(Array {
                 elements: vec![
-                    Element::Number(1.0),
-                    Element::String("string".to_string()),
+                    Element::Number(Number::from(1)),
+                    Element::String("string".into()),
                     Element::Null
                 ]
             })
@@ -353,18 +856,579 @@ mod test {
         println!("{:?}", result);
         assert_eq!(
             result,
-            Element::Object(Object {
-                members: vec![Member {
-                    key: "key".to_string(),
-                    value: Element::Array(Array {
-                        elements: vec![
-                            Element::Number(1.0),
-                            Element::String("string".to_string()),
-                            Element::Null
-                        ]
-                    })
-                }]
+            Element::Object(Object::from_members(vec![Member {
+                key: "key".into(),
+                value: Element::Array(Array {
+                    elements: vec![
+                        Element::Number(Number::from(1)),
+                        Element::String("string".into()),
+                        Element::Null
+                    ]
+                })
+            }]))
+        );
+    }
+
+    #[test]
+    fn parse_with_options() {
+        let strict = ParserOptions::default();
+        let trailing = ParserOptions {
+            allow_trailing_commas: true,
+            ..ParserOptions::default()
+        };
+        let missing = ParserOptions {
+            allow_missing_commas: true,
+            ..ParserOptions::default()
+        };
+        let both = ParserOptions {
+            allow_trailing_commas: true,
+            allow_missing_commas: true,
+            ..ParserOptions::default()
+        };
+
+        // (入力, strict, trailing, missing, both)
+        let cases = [
+            ("[1, 2]", true, true, true, true),
+            (r#"{"a": 1, "b": 2}"#, true, true, true, true),
+            ("[1, 2,]", false, true, false, true),
+            (r#"{"a": 1,}"#, false, true, false, true),
+            ("[1 2]", false, false, true, true),
+            (r#"{"a": 1 "b": 2}"#, false, false, true, true),
+            ("[1 2,]", false, false, false, true),
+            ("[,]", false, false, false, false),
+            ("[1,,2]", false, false, false, false),
+            (r#"{,"a": 1}"#, false, false, false, false),
+            ("[1 2", false, false, false, false),
+        ];
+
+        for (input, ok_strict, ok_trailing, ok_missing, ok_both) in cases {
+            for (options, ok) in [
+                (strict, ok_strict),
+                (trailing, ok_trailing),
+                (missing, ok_missing),
+                (both, ok_both),
+            ] {
+                let mut parser = get_parser_with_options(input.to_string(), options);
+                assert_eq!(parser.parse().is_ok(), ok, "{} {:?}", input, options);
+            }
+        }
+
+        let mut parser = get_parser_with_options("[1 2,]".to_string(), both);
+        assert_eq!(
+            parser.parse().unwrap(),
+            Element::Array(Array {
+                elements: vec![
+                    Element::Number(Number::from(1)),
+                    Element::Number(Number::from(2))
+                ]
+            })
+        );
+
+        let mut parser = get_parser("[1, 2,]".to_string());
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnexpectedToken {
+                token: Token::RightBracket,
+                span: Span {
+                    start: 6,
+                    end: 7,
+                    line: 1,
+                    column: 7
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn parse_duplicate_keys() {
+        let source = r#"{"a": 1, "b": 2, "a": 3}"#;
+        let number = |n: u64| Element::Number(Number::from(n));
+        let with_policy = |policy| {
+            get_parser_with_options(
+                source.to_string(),
+                ParserOptions {
+                    duplicate_keys: policy,
+                    ..ParserOptions::default()
+                },
+            )
+        };
+
+        let result = with_policy(DuplicateKeyPolicy::KeepAll).parse().unwrap();
+        assert_eq!(
+            result,
+            Element::Object(Object::from_members(vec![
+                Member {
+                    key: "a".into(),
+                    value: number(1),
+                },
+                Member {
+                    key: "b".into(),
+                    value: number(2),
+                },
+                Member {
+                    key: "a".into(),
+                    value: number(3),
+                },
+            ]))
+        );
+
+        let result = with_policy(DuplicateKeyPolicy::FirstWins).parse().unwrap();
+        assert_eq!(
+            result,
+            Element::Object(Object::from_iter([
+                ("a".to_string(), number(1)),
+                ("b".to_string(), number(2)),
+            ]))
+        );
+
+        let result = with_policy(DuplicateKeyPolicy::LastWins).parse().unwrap();
+        assert_eq!(
+            result,
+            Element::Object(Object::from_iter([
+                ("a".to_string(), number(3)),
+                ("b".to_string(), number(2)),
+            ]))
+        );
+
+        assert_eq!(
+            with_policy(DuplicateKeyPolicy::Error).parse(),
+            Err(ParseError::DuplicateKey {
+                key: "a".into(),
+                first: Span {
+                    start: 1,
+                    end: 4,
+                    line: 1,
+                    column: 2
+                },
+                second: Span {
+                    start: 17,
+                    end: 20,
+                    line: 1,
+                    column: 18
+                },
+            })
+        );
+
+        // 別のオブジェクトにある同じキーは重複ではない
+        let mut parser = get_parser_with_options(
+            r#"{"a": {"a": 1}, "b": {"a": 2}}"#.to_string(),
+            ParserOptions {
+                duplicate_keys: DuplicateKeyPolicy::Error,
+                ..ParserOptions::default()
+            },
+        );
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn parse_depth_limit() {
+        let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+
+        let mut parser = get_parser(nested(128));
+        assert!(parser.parse().is_ok());
+
+        let mut parser = get_parser(nested(129));
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::DepthLimitExceeded {
+                depth: 129,
+                span: Span {
+                    start: 128,
+                    end: 129,
+                    line: 1,
+                    column: 129
+                }
+            })
+        );
+
+        let mut parser = get_parser(r#"{"a": {"b": [1]}}"#.to_string());
+        assert!(parser.parse().is_ok());
+
+        let options = ParserOptions {
+            max_depth: 2,
+            ..ParserOptions::default()
+        };
+        let mut parser = get_parser_with_options(r#"{"a": {"b": [1]}}"#.to_string(), options);
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::DepthLimitExceeded { depth: 3, .. })
+        ));
+
+        // 兄弟要素は深さに影響しない
+        let mut parser = get_parser_with_options("[[], [], [1]]".to_string(), options);
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn parse_limits() {
+        let with_limits = |source: &str, limits| {
+            get_parser_with_options(
+                source.to_string(),
+                ParserOptions {
+                    limits,
+                    ..ParserOptions::default()
+                },
+            )
+            .parse()
+        };
+
+        let limits = ParserLimits {
+            max_object_members: Some(2),
+            ..ParserLimits::default()
+        };
+        assert!(with_limits(r#"{"a": 1, "b": {"c": 2, "d": 3}}"#, limits).is_ok());
+        assert_eq!(
+            with_limits(r#"{"a": 1, "b": 2, "c": 3}"#, limits),
+            Err(ParseError::TooManyMembers {
+                limit: 2,
+                span: Span {
+                    start: 17,
+                    end: 20,
+                    line: 1,
+                    column: 18
+                }
+            })
+        );
+
+        let limits = ParserLimits {
+            max_array_elements: Some(3),
+            ..ParserLimits::default()
+        };
+        assert!(with_limits("[1, 2, [3, 4, 5]]", limits).is_ok());
+        assert_eq!(
+            with_limits("[1, 2, 3, 4]", limits),
+            Err(ParseError::TooManyElements {
+                limit: 3,
+                span: Span {
+                    start: 10,
+                    end: 11,
+                    line: 1,
+                    column: 11
+                }
             })
         );
+
+        // コンテナ自身も1つの値として数える
+        let limits = ParserLimits {
+            max_nodes: Some(4),
+            ..ParserLimits::default()
+        };
+        assert!(with_limits(r#"{"a": [1, 2]}"#, limits).is_ok());
+        assert!(matches!(
+            with_limits(r#"{"a": [1, 2, 3]}"#, limits),
+            Err(ParseError::TooManyNodes { limit: 4, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_pathologically_deep_document() {
+        let source = "[".repeat(100_000) + &"{\"a\":".repeat(100_000);
+
+        let mut parser = get_parser(source);
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::DepthLimitExceeded { depth: 129, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_trailing_characters() {
+        let mut parser = get_parser("{} {}".to_string());
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::TrailingCharacters {
+                span: Span {
+                    start: 3,
+                    end: 4,
+                    line: 1,
+                    column: 4
+                }
+            })
+        );
+
+        let mut parser = get_parser("1 2".to_string());
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::TrailingCharacters { .. })
+        ));
+
+        let mut parser = get_parser("[1] ]".to_string());
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::TrailingCharacters { .. })
+        ));
+
+        let mut parser = get_parser("  [1]  \n".to_string());
+        assert!(parser.parse().is_ok());
+    }
+
+    #[test]
+    fn parse_all() {
+        let mut parser = get_parser("{} [1]\n\"a\" 2".to_string());
+        assert_eq!(
+            parser.parse_all().unwrap(),
+            vec![
+                Element::Object(Object::from_members(vec![])),
+                Element::Array(Array {
+                    elements: vec![Element::Number(Number::from(1))]
+                }),
+                Element::String("a".into()),
+                Element::Number(Number::from(2)),
+            ]
+        );
+
+        let mut parser = get_parser("  ".to_string());
+        assert_eq!(parser.parse_all().unwrap(), vec![]);
+
+        let mut parser = get_parser("{} ]".to_string());
+        assert!(matches!(
+            parser.parse_all(),
+            Err(ParseError::UnexpectedToken {
+                token: Token::RightBracket,
+                ..
+            })
+        ));
+
+        // 値の数の上限は文書ごとに数える
+        let options = ParserOptions {
+            limits: ParserLimits {
+                max_nodes: Some(2),
+                ..ParserLimits::default()
+            },
+            ..ParserOptions::default()
+        };
+        let mut parser = get_parser_with_options("1 2 3 [4]".to_string(), options);
+        assert_eq!(parser.parse_all().map(|nodes| nodes.len()), Ok(4));
+
+        let mut parser = get_parser_with_options("1 [2, 3]".to_string(), options);
+        assert!(matches!(
+            parser.parse_all(),
+            Err(ParseError::TooManyNodes { limit: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn parse_large_integer() {
+        let mut parser = get_parser("[18446744073709551615, 9007199254740993]".to_string());
+
+        let result = parser.parse().unwrap();
+        assert_eq!(
+            result,
+            Element::Array(Array {
+                elements: vec![
+                    Element::Number(Number::from(u64::MAX)),
+                    Element::Number(Number::from(9_007_199_254_740_993u64)),
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        let mut parser = get_parser(r#"{"a" 1}"#.to_string());
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnexpectedToken {
+                token: Token::Number(Number::from(1)),
+                span: Span {
+                    start: 5,
+                    end: 6,
+                    line: 1,
+                    column: 6
+                }
+            })
+        );
+
+        let mut parser = get_parser("[1,\n 2".to_string());
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnexpectedEof {
+                span: Span {
+                    start: 6,
+                    end: 6,
+                    line: 2,
+                    column: 3
+                }
+            })
+        );
+
+        let mut parser = get_parser(r#"{"a": }"#.to_string());
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnexpectedToken {
+                token: Token::RightBrace,
+                span: Span {
+                    start: 6,
+                    end: 7,
+                    line: 1,
+                    column: 7
+                }
+            })
+        );
+
+        let mut parser = Parser::new(std::iter::empty());
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::UnexpectedEof {
+                span: Span::default()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_lazy_tokens() {
+        use crate::tokenizer::Tokenizer;
+
+        let mut parser = Parser::new(Tokenizer::new(r#"{"key": [1, true, null]}"#));
+        assert_eq!(
+            parser.parse(),
+            Ok(Element::Object(Object::from_iter([(
+                "key".to_string(),
+                Element::Array(Array::from_iter([
+                    Element::Number(Number::from(1)),
+                    Element::Boolean(true),
+                    Element::Null,
+                ]))
+            )])))
+        );
+
+        // トークナイザーのエラーはそのまま返す
+        let mut parser = Parser::new(Tokenizer::new("[1, @]"));
+        assert_eq!(
+            parser.parse(),
+            Err(ParseError::Tokenize(TokenizerError::InvalidCharacter {
+                character: '@',
+                span: Span {
+                    start: 4,
+                    end: 5,
+                    line: 1,
+                    column: 5
+                }
+            }))
+        );
+
+        // 構文エラーより後ろはトークナイズしない
+        let mut parser = Parser::new(Tokenizer::new("[1 2 @]"));
+        assert!(matches!(
+            parser.parse(),
+            Err(ParseError::UnexpectedToken { .. })
+        ));
+    }
+
+    // イベントを文字列として記録するハンドラー
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl<'a> JsonHandler<'a> for Recorder {
+        type Error = ParseError;
+
+        fn start_object(&mut self, _: Span) -> Result<(), ParseError> {
+            self.0.push("{".to_string());
+            Ok(())
+        }
+
+        fn key(&mut self, key: Cow<'a, str>, span: Span) -> Result<(), ParseError> {
+            self.0.push(format!("{}@{}", key, span.start));
+            Ok(())
+        }
+
+        fn end_object(&mut self, _: Span) -> Result<(), ParseError> {
+            self.0.push("}".to_string());
+            Ok(())
+        }
+
+        fn start_array(&mut self, _: Span) -> Result<(), ParseError> {
+            self.0.push("[".to_string());
+            Ok(())
+        }
+
+        fn end_array(&mut self, _: Span) -> Result<(), ParseError> {
+            self.0.push("]".to_string());
+            Ok(())
+        }
+
+        fn string(&mut self, value: Cow<'a, str>, _: Span) -> Result<(), ParseError> {
+            self.0.push(format!("{:?}", value));
+            Ok(())
+        }
+
+        fn number(&mut self, value: Number, _: Span) -> Result<(), ParseError> {
+            self.0.push(value.to_string());
+            Ok(())
+        }
+
+        fn bool(&mut self, value: bool, _: Span) -> Result<(), ParseError> {
+            self.0.push(value.to_string());
+            Ok(())
+        }
+
+        fn null(&mut self, _: Span) -> Result<(), ParseError> {
+            self.0.push("null".to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parse_with_handler() {
+        let mut recorder = Recorder::default();
+        let mut parser = get_parser(r#"{"a": [1, "x", true, null], "b": {}}"#.to_string());
+        parser.parse_with(&mut recorder).unwrap();
+
+        assert_eq!(
+            recorder.0,
+            vec!["{", "a@1", "[", "1", "\"x\"", "true", "null", "]", "b@28", "{", "}", "}"]
+        );
+
+        // 構文エラーはイベントの途中でも返す
+        let mut recorder = Recorder::default();
+        let mut parser = get_parser("[1, 2".to_string());
+        assert!(matches!(
+            parser.parse_with(&mut recorder),
+            Err(ParseError::UnexpectedEof { .. })
+        ));
+        assert_eq!(recorder.0, vec!["[", "1", "2"]);
+    }
+
+    #[test]
+    fn parse_with_handler_error() {
+        #[derive(Debug, PartialEq)]
+        enum StopError {
+            Parse(ParseError),
+            Null(Span),
+        }
+
+        impl From<ParseError> for StopError {
+            fn from(err: ParseError) -> Self {
+                StopError::Parse(err)
+            }
+        }
+
+        // 最初のnullで止めるハンドラー
+        struct StopAtNull;
+
+        impl JsonHandler<'_> for StopAtNull {
+            type Error = StopError;
+
+            fn null(&mut self, span: Span) -> Result<(), StopError> {
+                Err(StopError::Null(span))
+            }
+        }
+
+        let mut parser = Parser::new(crate::tokenizer::Tokenizer::new("[1, null, @"));
+        assert_eq!(
+            parser.parse_with(&mut StopAtNull),
+            Err(StopError::Null(Span {
+                start: 4,
+                end: 8,
+                line: 1,
+                column: 5
+            }))
+        );
+
+        let mut parser = Parser::new(crate::tokenizer::Tokenizer::new("[1, @"));
+        assert!(matches!(
+            parser.parse_with(&mut StopAtNull),
+            Err(StopError::Parse(ParseError::Tokenize(_)))
+        ));
     }
 }
@@ -0,0 +1,334 @@
+use std::{fmt, io};
+
+use crate::element::{Array, Element, Member, Object};
+use crate::serializer::{write_number, write_string};
+
+// 行幅を計算するときのタブの幅
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs,
+}
+
+/// Renders an `Element` as indented, human readable JSON.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PrettyPrinter {
+    pub indent: Indent,
+    // オブジェクトのキーを辞書順に並べる
+    pub sort_keys: bool,
+    // この幅に収まる配列は1行で出力する (Noneなら常に改行する)
+    pub max_width: Option<usize>,
+    pub trailing_newline: bool,
+    // ASCII以外の文字を \uXXXX にエスケープする
+    pub ascii_only: bool,
+}
+
+impl Default for PrettyPrinter {
+    fn default() -> Self {
+        PrettyPrinter {
+            indent: Indent::Spaces(2),
+            sort_keys: false,
+            max_width: None,
+            trailing_newline: false,
+            ascii_only: false,
+        }
+    }
+}
+
+impl PrettyPrinter {
+    pub fn print(&self, element: &Element) -> String {
+        self.display(element).to_string()
+    }
+
+    pub fn to_writer<W: io::Write>(&self, element: &Element, mut writer: W) -> io::Result<()> {
+        write!(writer, "{}", self.display(element))
+    }
+
+    fn display<'a>(&'a self, element: &'a Element<'a>) -> Pretty<'a> {
+        Pretty {
+            printer: self,
+            element,
+        }
+    }
+
+    // usedはこの要素と同じ行に出力される前後の文字の幅
+    fn write_element<W: fmt::Write>(
+        &self,
+        w: &mut W,
+        element: &Element,
+        level: usize,
+        used: usize,
+    ) -> fmt::Result {
+        match element {
+            Element::Object(object) if !object.members().is_empty() => {
+                let members = self.members(object);
+
+                w.write_str("{\n")?;
+                for (i, member) in members.iter().enumerate() {
+                    let mut key = String::new();
+                    write_string(&mut key, member.key(), self.ascii_only)?;
+
+                    let comma = usize::from(i + 1 < members.len());
+                    let used = self.indent_width(level + 1) + key.chars().count() + 2 + comma;
+
+                    self.write_indent(w, level + 1)?;
+                    w.write_str(&key)?;
+                    w.write_str(": ")?;
+                    self.write_element(w, member.value(), level + 1, used)?;
+                    if comma == 1 {
+                        w.write_char(',')?;
+                    }
+                    w.write_char('\n')?;
+                }
+                self.write_indent(w, level)?;
+                w.write_char('}')
+            }
+            Element::Array(array) if !array.elements().is_empty() => {
+                if let Some(max_width) = self.max_width {
+                    if is_compactable(array) {
+                        let mut line = String::new();
+                        self.write_compact(&mut line, element)?;
+
+                        if used + line.chars().count() <= max_width {
+                            return w.write_str(&line);
+                        }
+                    }
+                }
+
+                let elements = array.elements();
+
+                w.write_str("[\n")?;
+                for (i, element) in elements.iter().enumerate() {
+                    let comma = usize::from(i + 1 < elements.len());
+                    let used = self.indent_width(level + 1) + comma;
+
+                    self.write_indent(w, level + 1)?;
+                    self.write_element(w, element, level + 1, used)?;
+                    if comma == 1 {
+                        w.write_char(',')?;
+                    }
+                    w.write_char('\n')?;
+                }
+                self.write_indent(w, level)?;
+                w.write_char(']')
+            }
+            _ => self.write_compact(w, element),
+        }
+    }
+
+    // 改行せずに1行で出力する
+    fn write_compact<W: fmt::Write>(&self, w: &mut W, element: &Element) -> fmt::Result {
+        match element {
+            Element::String(str) => write_string(w, str, self.ascii_only),
+            Element::Number(num) => write_number(w, num),
+            Element::Boolean(val) => write!(w, "{}", val),
+            Element::Null => w.write_str("null"),
+            Element::Object(object) => {
+                w.write_char('{')?;
+                for (i, member) in self.members(object).iter().enumerate() {
+                    if i > 0 {
+                        w.write_str(", ")?;
+                    }
+                    write_string(w, member.key(), self.ascii_only)?;
+                    w.write_str(": ")?;
+                    self.write_compact(w, member.value())?;
+                }
+                w.write_char('}')
+            }
+            Element::Array(array) => {
+                w.write_char('[')?;
+                for (i, element) in array.elements().iter().enumerate() {
+                    if i > 0 {
+                        w.write_str(", ")?;
+                    }
+                    self.write_compact(w, element)?;
+                }
+                w.write_char(']')
+            }
+        }
+    }
+
+    fn members<'a>(&self, object: &'a Object<'a>) -> Vec<&'a Member<'a>> {
+        let mut members: Vec<&Member> = object.members().iter().collect();
+
+        if self.sort_keys {
+            members.sort_by(|a, b| a.key().cmp(b.key()));
+        }
+
+        members
+    }
+
+    fn write_indent<W: fmt::Write>(&self, w: &mut W, level: usize) -> fmt::Result {
+        match self.indent {
+            Indent::Spaces(width) => write!(w, "{:1$}", "", width * level),
+            Indent::Tabs => (0..level).try_for_each(|_| w.write_char('\t')),
+        }
+    }
+
+    fn indent_width(&self, level: usize) -> usize {
+        match self.indent {
+            Indent::Spaces(width) => width * level,
+            Indent::Tabs => TAB_WIDTH * level,
+        }
+    }
+}
+
+// 空でないオブジェクトを含む配列は1行にしない
+fn is_compactable(array: &Array) -> bool {
+    array.elements().iter().all(|element| match element {
+        Element::Object(object) => object.members().is_empty(),
+        Element::Array(array) => is_compactable(array),
+        _ => true,
+    })
+}
+
+struct Pretty<'a> {
+    printer: &'a PrettyPrinter,
+    element: &'a Element<'a>,
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.printer.write_element(f, self.element, 0, 0)?;
+
+        if self.printer.trailing_newline {
+            f.write_str("\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::Rng;
+
+    const SOURCE: &str = r#"{"name": "山田太郎", "tags": [1, 2, 3], "address": {"city": "東京", "zip": "123-4567"}, "empty": {}, "list": [], "matrix": [[1, 2], [3, 4]], "objects": [{"a": 1}]}"#;
+
+    #[test]
+    fn print_default() {
+        let element = crate::parse(SOURCE).unwrap();
+
+        assert_eq!(
+            PrettyPrinter::default().print(&element),
+            r#"{
+  "name": "山田太郎",
+  "tags": [
+    1,
+    2,
+    3
+  ],
+  "address": {
+    "city": "東京",
+    "zip": "123-4567"
+  },
+  "empty": {},
+  "list": [],
+  "matrix": [
+    [
+      1,
+      2
+    ],
+    [
+      3,
+      4
+    ]
+  ],
+  "objects": [
+    {
+      "a": 1
+    }
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn print_with_options() {
+        let element = crate::parse(SOURCE).unwrap();
+
+        let printer = PrettyPrinter {
+            indent: Indent::Tabs,
+            sort_keys: true,
+            max_width: Some(24),
+            trailing_newline: true,
+            ascii_only: true,
+        };
+
+        assert_eq!(
+            printer.print(&element),
+            "{\n\t\"address\": {\n\t\t\"city\": \"\\u6771\\u4eac\",\n\t\t\"zip\": \"123-4567\"\n\t},\n\t\"empty\": {},\n\t\"list\": [],\n\t\"matrix\": [\n\t\t[1, 2],\n\t\t[3, 4]\n\t],\n\t\"name\": \"\\u5c71\\u7530\\u592a\\u90ce\",\n\t\"objects\": [\n\t\t{\n\t\t\t\"a\": 1\n\t\t}\n\t],\n\t\"tags\": [1, 2, 3]\n}\n"
+        );
+    }
+
+    #[test]
+    fn print_max_width() {
+        let element = crate::parse(r#"{"key": [1, 2, 3]}"#).unwrap();
+
+        // `  "key": [1, 2, 3]` は18文字
+        let printer = PrettyPrinter {
+            max_width: Some(18),
+            ..PrettyPrinter::default()
+        };
+        assert_eq!(printer.print(&element), "{\n  \"key\": [1, 2, 3]\n}");
+
+        let printer = PrettyPrinter {
+            max_width: Some(17),
+            ..PrettyPrinter::default()
+        };
+        assert_eq!(
+            printer.print(&element),
+            "{\n  \"key\": [\n    1,\n    2,\n    3\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn print_ascii_only() {
+        let printer = PrettyPrinter {
+            ascii_only: true,
+            ..PrettyPrinter::default()
+        };
+
+        let element = Element::String("こんにちは😀".into());
+        assert_eq!(
+            printer.print(&element),
+            r#""\u3053\u3093\u306b\u3061\u306f\ud83d\ude00""#
+        );
+
+        let mut buf = vec![];
+        printer.to_writer(&element, &mut buf).unwrap();
+        assert!(buf.is_ascii());
+        assert_eq!(
+            crate::parse(std::str::from_utf8(&buf).unwrap()),
+            Ok(element)
+        );
+    }
+
+    #[test]
+    fn print_round_trip() {
+        let printers = [
+            PrettyPrinter::default(),
+            PrettyPrinter {
+                indent: Indent::Tabs,
+                max_width: Some(40),
+                trailing_newline: true,
+                ascii_only: true,
+                ..PrettyPrinter::default()
+            },
+        ];
+
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..500 {
+            let element = rng.element(4);
+
+            for printer in &printers {
+                let json = printer.print(&element);
+                assert_eq!(crate::parse(&json).as_ref(), Ok(&element), "{}", json);
+            }
+        }
+    }
+}
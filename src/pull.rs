@@ -0,0 +1,450 @@
+use std::borrow::Cow;
+
+use crate::number::Number;
+use crate::parser::{unexpected, ParseError, Parser, ParserOptions};
+use crate::span::Span;
+use crate::tokenizer::{Token, TokenizerError};
+
+/// A piece of a document returned by `JsonReader`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    // メンバーのキー。次のイベントがその値
+    Key(Cow<'a, str>),
+    String(Cow<'a, str>),
+    Number(Number),
+    Bool(bool),
+    Null,
+}
+
+// 開いているコンテナと、これまでのメンバーか要素の数
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+    Object(usize),
+    Array(usize),
+}
+
+// 次に読むもの
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    Value,
+    FirstMember,
+    NextMember,
+    FirstElement,
+    NextElement,
+    // ルートの値を読み終えた
+    End,
+    Finished,
+}
+
+/// Reads a document one event at a time, leaving it to the caller which
+/// parts to keep.
+///
+/// Parser options apply as they do to `Parser`, except for the duplicate key
+/// policy since no objects are built.
+pub struct JsonReader<'a, I: Iterator> {
+    parser: Parser<I>,
+    stack: Vec<Frame>,
+    state: State,
+    // peek_eventで先読みしたイベント
+    peeked: Option<Option<Event<'a>>>,
+}
+
+impl<'a, I> JsonReader<'a, I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    pub fn new(tokens: I) -> Self {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    pub fn with_options(tokens: I, options: ParserOptions) -> Self {
+        JsonReader {
+            parser: Parser::with_options(tokens, options),
+            stack: vec![],
+            state: State::Value,
+            peeked: None,
+        }
+    }
+
+    /// Returns the next event, or `None` once the document has been read.
+    pub fn next_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        match self.peeked.take() {
+            Some(event) => Ok(event),
+            None => self.read_event(),
+        }
+    }
+
+    pub fn peek_event(&mut self) -> Result<Option<&Event<'a>>, ParseError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_event()?);
+        }
+
+        Ok(self.peeked.as_ref().and_then(Option::as_ref))
+    }
+
+    /// Skips the next value along with everything inside it. Before a key,
+    /// the key and its value are skipped.
+    pub fn skip_value(&mut self) -> Result<(), ParseError> {
+        if let Some(Event::Key(_)) = self.peek_event()? {
+            self.next_event()?;
+        }
+
+        match self.peek_event()? {
+            Some(Event::StartObject | Event::StartArray) => {
+                let depth = self.depth();
+
+                self.next_event()?;
+                while self.depth() > depth {
+                    if self.next_event()?.is_none() {
+                        break;
+                    }
+                }
+            }
+            // 閉じ括弧や文書の終わりの前には飛ばす値がない
+            Some(Event::EndObject | Event::EndArray) | None => {}
+            Some(_) => {
+                self.next_event()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Number of objects and arrays the events returned so far are inside.
+    pub fn depth(&self) -> usize {
+        // 先読みしたイベントの分は数えない
+        match &self.peeked {
+            Some(Some(Event::StartObject | Event::StartArray)) => self.stack.len() - 1,
+            Some(Some(Event::EndObject | Event::EndArray)) => self.stack.len() + 1,
+            _ => self.stack.len(),
+        }
+    }
+
+    // エラーの後は何も返さない
+    fn read_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        let result = self.parse_event();
+
+        if result.is_err() {
+            self.state = State::Finished;
+            self.peeked = None;
+        }
+
+        result
+    }
+
+    fn parse_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        let event = match self.state {
+            State::Value => self.read_value()?,
+            State::FirstMember => {
+                if let Token::RightBrace = self.parser.peek_token()? {
+                    self.close()?
+                } else {
+                    self.read_key()?
+                }
+            }
+            State::NextMember => {
+                if self.parser.parse_separator(Token::RightBrace)? {
+                    self.close()?
+                } else {
+                    self.read_key()?
+                }
+            }
+            State::FirstElement => {
+                if let Token::RightBracket = self.parser.peek_token()? {
+                    self.close()?
+                } else {
+                    self.read_element()?
+                }
+            }
+            State::NextElement => {
+                if self.parser.parse_separator(Token::RightBracket)? {
+                    self.close()?
+                } else {
+                    self.read_element()?
+                }
+            }
+            State::End => {
+                self.parser.parse_end()?;
+                self.state = State::Finished;
+                return Ok(None);
+            }
+            State::Finished => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+
+    fn read_value(&mut self) -> Result<Event<'a>, ParseError> {
+        let (token, span) = self.parser.next_token()?;
+
+        self.parser.count_node(span)?;
+
+        let event = match token {
+            Token::String(val) => Event::String(val),
+            Token::Number(val) => Event::Number(val),
+            Token::Bool(val) => Event::Bool(val),
+            Token::Null => Event::Null,
+            Token::LeftBrace => {
+                self.parser.enter(span)?;
+                self.stack.push(Frame::Object(0));
+                self.state = State::FirstMember;
+                return Ok(Event::StartObject);
+            }
+            Token::LeftBracket => {
+                self.parser.enter(span)?;
+                self.stack.push(Frame::Array(0));
+                self.state = State::FirstElement;
+                return Ok(Event::StartArray);
+            }
+            token => return Err(unexpected(token, span)),
+        };
+
+        self.after_value();
+
+        Ok(event)
+    }
+
+    fn read_key(&mut self) -> Result<Event<'a>, ParseError> {
+        let (key, key_span) = match self.parser.next_token()? {
+            (Token::String(key), span) => (key, span),
+            (token, span) => return Err(unexpected(token, span)),
+        };
+
+        if let Some(Frame::Object(count)) = self.stack.last_mut() {
+            *count += 1;
+            let count = *count;
+            self.parser.count_member(count, key_span)?;
+        }
+
+        match self.parser.next_token()? {
+            (Token::Colon, _) => {}
+            (token, span) => return Err(unexpected(token, span)),
+        }
+
+        self.state = State::Value;
+
+        Ok(Event::Key(key))
+    }
+
+    fn read_element(&mut self) -> Result<Event<'a>, ParseError> {
+        if let Some(Frame::Array(count)) = self.stack.last_mut() {
+            *count += 1;
+            let count = *count;
+            self.parser.count_element(count)?;
+        }
+
+        self.read_value()
+    }
+
+    // 閉じ括弧を読んでコンテナを閉じる
+    fn close(&mut self) -> Result<Event<'a>, ParseError> {
+        let (token, span) = self.parser.next_token()?;
+
+        let event = match (self.stack.pop(), token) {
+            (Some(Frame::Object(_)), Token::RightBrace) => Event::EndObject,
+            (Some(Frame::Array(_)), Token::RightBracket) => Event::EndArray,
+            (_, token) => return Err(unexpected(token, span)),
+        };
+
+        self.parser.leave();
+        self.after_value();
+
+        Ok(event)
+    }
+
+    fn after_value(&mut self) {
+        self.state = match self.stack.last() {
+            Some(Frame::Object(_)) => State::NextMember,
+            Some(Frame::Array(_)) => State::NextElement,
+            None => State::End,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::tokenizer::Tokenizer;
+
+    fn get_reader(str: &str) -> JsonReader<'_, Tokenizer<'_>> {
+        JsonReader::new(Tokenizer::new(str))
+    }
+
+    fn events<'a>(
+        reader: &mut JsonReader<'a, Tokenizer<'a>>,
+    ) -> Result<Vec<Event<'a>>, ParseError> {
+        let mut events = vec![];
+
+        while let Some(event) = reader.next_event()? {
+            events.push(event);
+        }
+
+        Ok(events)
+    }
+
+    #[test]
+    fn read_events() {
+        let mut reader = get_reader(r#"{"a": [1, "x", true, null, {}], "b": []}"#);
+
+        assert_eq!(
+            events(&mut reader),
+            Ok(vec![
+                Event::StartObject,
+                Event::Key("a".into()),
+                Event::StartArray,
+                Event::Number(Number::from(1)),
+                Event::String("x".into()),
+                Event::Bool(true),
+                Event::Null,
+                Event::StartObject,
+                Event::EndObject,
+                Event::EndArray,
+                Event::Key("b".into()),
+                Event::StartArray,
+                Event::EndArray,
+                Event::EndObject,
+            ])
+        );
+        assert_eq!(reader.next_event(), Ok(None));
+
+        let mut reader = get_reader("42");
+        assert_eq!(
+            events(&mut reader),
+            Ok(vec![Event::Number(Number::from(42))])
+        );
+    }
+
+    #[test]
+    fn peek_and_depth() {
+        let mut reader = get_reader(r#"[{"a": 1}]"#);
+        assert_eq!(reader.depth(), 0);
+
+        assert_eq!(reader.peek_event(), Ok(Some(&Event::StartArray)));
+        assert_eq!(reader.depth(), 0);
+        assert_eq!(reader.next_event(), Ok(Some(Event::StartArray)));
+        assert_eq!(reader.depth(), 1);
+
+        assert_eq!(reader.next_event(), Ok(Some(Event::StartObject)));
+        assert_eq!(reader.next_event(), Ok(Some(Event::Key("a".into()))));
+        assert_eq!(reader.depth(), 2);
+        assert_eq!(
+            reader.next_event(),
+            Ok(Some(Event::Number(Number::from(1))))
+        );
+
+        assert_eq!(reader.peek_event(), Ok(Some(&Event::EndObject)));
+        assert_eq!(reader.depth(), 2);
+        assert_eq!(reader.next_event(), Ok(Some(Event::EndObject)));
+        assert_eq!(reader.depth(), 1);
+        assert_eq!(reader.next_event(), Ok(Some(Event::EndArray)));
+        assert_eq!(reader.depth(), 0);
+        assert_eq!(reader.peek_event(), Ok(None));
+        assert_eq!(reader.next_event(), Ok(None));
+    }
+
+    #[test]
+    fn skip_values() {
+        let source = r#"[
+            {"id": 1, "payload": {"deep": [1, [2, {"x": 3}]]}, "name": "a"},
+            {"name": "b", "payload": [], "id": 2}
+        ]"#;
+        let mut reader = get_reader(source);
+
+        // 各レコードのidだけを取り出す
+        let mut ids = vec![];
+        assert_eq!(reader.next_event(), Ok(Some(Event::StartArray)));
+        while let Ok(Some(Event::StartObject)) = reader.next_event() {
+            while let Ok(Some(Event::Key(key))) = reader.peek_event().map(|event| event.cloned()) {
+                if key == "id" {
+                    reader.next_event().unwrap();
+                    ids.push(reader.next_event().unwrap());
+                } else {
+                    reader.skip_value().unwrap();
+                }
+            }
+            assert_eq!(reader.next_event(), Ok(Some(Event::EndObject)));
+        }
+        assert_eq!(reader.depth(), 0);
+        assert_eq!(reader.next_event(), Ok(None));
+
+        assert_eq!(
+            ids,
+            vec![
+                Some(Event::Number(Number::from(1))),
+                Some(Event::Number(Number::from(2)))
+            ]
+        );
+
+        // 閉じ括弧の前では何もしない
+        let mut reader = get_reader("[[1, 2], 3]");
+        reader.next_event().unwrap();
+        reader.skip_value().unwrap();
+        assert_eq!(reader.depth(), 1);
+        reader.skip_value().unwrap();
+        reader.skip_value().unwrap();
+        assert_eq!(reader.next_event(), Ok(Some(Event::EndArray)));
+    }
+
+    #[test]
+    fn read_errors() {
+        let mut reader = get_reader(r#"{"a" 1}"#);
+        assert_eq!(
+            events(&mut reader),
+            Err(ParseError::UnexpectedToken {
+                token: Token::Number(Number::from(1)),
+                span: Span {
+                    start: 5,
+                    end: 6,
+                    line: 1,
+                    column: 6
+                }
+            })
+        );
+
+        let mut reader = get_reader("[1, 2] 3");
+        assert!(matches!(
+            events(&mut reader),
+            Err(ParseError::TrailingCharacters { .. })
+        ));
+
+        let mut reader = get_reader("[1, 2");
+        assert!(matches!(
+            events(&mut reader),
+            Err(ParseError::UnexpectedEof { .. })
+        ));
+
+        let mut reader = get_reader("[1, ]");
+        assert!(matches!(
+            events(&mut reader),
+            Err(ParseError::UnexpectedToken { .. })
+        ));
+
+        let options = ParserOptions {
+            allow_trailing_commas: true,
+            max_depth: 2,
+            ..ParserOptions::default()
+        };
+        let mut reader = JsonReader::with_options(Tokenizer::new("[[1, ], ]"), options);
+        assert!(events(&mut reader).is_ok());
+
+        let mut reader = JsonReader::with_options(Tokenizer::new("[[[]]]"), options);
+        assert!(matches!(
+            events(&mut reader),
+            Err(ParseError::DepthLimitExceeded { depth: 3, .. })
+        ));
+
+        // エラーの後はイベントを返さない
+        let mut reader = JsonReader::with_options(Tokenizer::new("[[[1]]]"), options);
+        assert!(matches!(
+            events(&mut reader),
+            Err(ParseError::DepthLimitExceeded { .. })
+        ));
+        assert_eq!(reader.next_event(), Ok(None));
+        assert_eq!(reader.peek_event(), Ok(None));
+        assert_eq!(reader.skip_value(), Ok(()));
+    }
+}
@@ -0,0 +1,99 @@
+use std::io::{self, Read};
+
+// 読み込みバッファの大きさ
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Reads bytes from a reader through a fixed-size buffer.
+pub(crate) struct ReadBytes<'a> {
+    reader: Box<dyn Read + 'a>,
+    buf: Box<[u8]>,
+    // buf[pos..len] がまだ読んでいないバイト
+    pos: usize,
+    len: usize,
+}
+
+impl<'a> ReadBytes<'a> {
+    pub(crate) fn new<R: Read + 'a>(reader: R) -> Self {
+        Self::with_capacity(reader, BUFFER_SIZE)
+    }
+
+    pub(crate) fn with_capacity<R: Read + 'a>(reader: R, capacity: usize) -> Self {
+        ReadBytes {
+            reader: Box::new(reader),
+            buf: vec![0; capacity.max(1)].into_boxed_slice(),
+            pos: 0,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn peek(&mut self) -> Result<Option<u8>, io::ErrorKind> {
+        if self.pos == self.len {
+            self.fill()?;
+        }
+
+        Ok(self.buf[self.pos..self.len].first().copied())
+    }
+
+    pub(crate) fn consume(&mut self) {
+        self.pos = (self.pos + 1).min(self.len);
+    }
+
+    fn fill(&mut self) -> Result<(), io::ErrorKind> {
+        loop {
+            match self.reader.read(&mut self.buf) {
+                Ok(n) => {
+                    self.pos = 0;
+                    self.len = n;
+                    return Ok(());
+                }
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.kind()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read_all(reader: &mut ReadBytes) -> Result<Vec<u8>, io::ErrorKind> {
+        let mut bytes = vec![];
+
+        while let Some(byte) = reader.peek()? {
+            bytes.push(byte);
+            reader.consume();
+        }
+
+        Ok(bytes)
+    }
+
+    #[test]
+    fn small_buffers() {
+        let text = "aあ😀bé日本語\u{ffff}".as_bytes();
+
+        for capacity in 1..8 {
+            let mut reader = ReadBytes::with_capacity(text, capacity);
+            assert_eq!(read_all(&mut reader).as_deref(), Ok(text));
+        }
+    }
+
+    #[test]
+    fn interrupted_reads_are_retried() {
+        struct Interrupting(bool);
+
+        impl Read for Interrupting {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                self.0 = !self.0;
+                if self.0 {
+                    return Err(io::ErrorKind::Interrupted.into());
+                }
+                buf[0] = b'x';
+                Ok(1)
+            }
+        }
+
+        let mut reader = ReadBytes::new(Read::take(Interrupting(false), 3));
+        assert_eq!(read_all(&mut reader), Ok(b"xxx".to_vec()));
+    }
+}
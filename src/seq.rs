@@ -0,0 +1,223 @@
+use crate::element::Element;
+use crate::parser::{unexpected, ParseError, Parser, ParserOptions};
+use crate::span::Span;
+use crate::tokenizer::{Token, TokenizerError};
+
+/// Reads an RFC 7464 JSON text sequence (`application/json-seq`), yielding
+/// the element of each record. The tokens must come from a `Tokenizer` with
+/// `json_seq` enabled.
+///
+/// A record that fails to parse is reported and skipped, and reading resumes
+/// at the next record separator.
+pub struct JsonSeqReader<I: Iterator> {
+    parser: Parser<I>,
+    // 直前のエラーで次のレコードの区切りまで読んでいる
+    separated: bool,
+    finished: bool,
+}
+
+impl<'a, I> JsonSeqReader<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    pub fn new(tokens: I) -> Self {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    /// The limits in `ParserOptions` apply to each record separately.
+    pub fn with_options(tokens: I, options: ParserOptions) -> Self {
+        JsonSeqReader {
+            parser: Parser::with_options(tokens, options),
+            separated: false,
+            finished: false,
+        }
+    }
+
+    fn next_record(&mut self) -> Result<Option<Element<'a>>, ParseError> {
+        // 連続した区切りは空のレコードとして読み飛ばす
+        loop {
+            if !std::mem::take(&mut self.separated) {
+                match self.parser.next_token()? {
+                    (Token::End, _) => return Ok(None),
+                    (Token::RecordSeparator, _) => {}
+                    (token, span) => return Err(unexpected(token, span)),
+                }
+            }
+
+            if !matches!(
+                self.parser.peek_token()?,
+                Token::RecordSeparator | Token::End
+            ) {
+                break;
+            }
+        }
+
+        self.parser.reset_nodes();
+
+        let element = self.parser.parse_element()?;
+        let span = self.parser.last_span();
+
+        if !matches!(
+            self.parser.peek_token()?,
+            Token::RecordSeparator | Token::End
+        ) {
+            return Err(ParseError::TrailingCharacters {
+                span: self.parser.peek_span(),
+            });
+        }
+
+        // 後ろに空白がない数値やリテラルは途中で切れた可能性がある (RFC 7464 2.4)
+        let scalar = matches!(
+            element,
+            Element::Number(_) | Element::Boolean(_) | Element::Null
+        );
+        if scalar && self.parser.peek_span().start == span.end {
+            return Err(ParseError::TruncatedRecord { span });
+        }
+
+        Ok(Some(element))
+    }
+
+    // 次の区切りの直前までトークンを読み飛ばす
+    fn skip_record(&mut self) {
+        loop {
+            match self.parser.peek_token() {
+                Ok(Token::RecordSeparator | Token::End) => break,
+                Err(err) if is_fatal(&err) => break,
+                _ => {
+                    let _ = self.parser.next_token();
+                }
+            }
+        }
+    }
+}
+
+impl<'a, I> Iterator for JsonSeqReader<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    type Item = Result<Element<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        match self.next_record() {
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Ok(Some(element)) => Some(Ok(element)),
+            Err(err) => {
+                match &err {
+                    err if is_fatal(err) => self.finished = true,
+                    ParseError::UnexpectedToken {
+                        token: Token::RecordSeparator,
+                        ..
+                    } => self.separated = true,
+                    _ => self.skip_record(),
+                }
+
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+// 入力が尽きたか、読み込みに失敗して続けられない
+fn is_fatal(err: &ParseError) -> bool {
+    matches!(
+        err,
+        ParseError::UnexpectedEof { .. }
+            | ParseError::Tokenize(
+                TokenizerError::Io { .. } | TokenizerError::InputTooLarge { .. }
+            )
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::Trickle;
+    use crate::tokenizer::{Tokenizer, TokenizerOptions};
+
+    fn records(input: &[u8]) -> Vec<Result<Element<'_>, ParseError>> {
+        let options = TokenizerOptions {
+            json_seq: true,
+            ..TokenizerOptions::default()
+        };
+
+        JsonSeqReader::new(Tokenizer::from_slice_with_options(input, options)).collect()
+    }
+
+    #[test]
+    fn read_records() {
+        let input = b"\x1e{\"a\": [1, 2]}\n\x1e\x1e\"s\"\n\x1e42\n\x1e";
+        let expected = vec![
+            crate::parse(r#"{"a": [1, 2]}"#).unwrap(),
+            Element::String("s".into()),
+            crate::parse("42").unwrap(),
+        ];
+
+        assert_eq!(
+            records(input),
+            expected.iter().cloned().map(Ok).collect::<Vec<_>>()
+        );
+
+        let read: Result<Vec<Element>, ParseError> =
+            crate::parse_json_seq(Trickle(input)).collect();
+        assert_eq!(read, Ok(expected));
+
+        assert_eq!(records(b""), vec![]);
+    }
+
+    #[test]
+    fn truncated_records() {
+        let input = b"\x1e{\"a\":\x1e1\n\x1e12\x1e\"ok\"\n\x1etr\x1enull\n\x1e[\"abc\x1etrue\n";
+        let res = records(input);
+
+        assert_eq!(res.len(), 8);
+        assert!(matches!(
+            res[0],
+            Err(ParseError::UnexpectedToken {
+                token: Token::RecordSeparator,
+                ..
+            })
+        ));
+        assert_eq!(res[1], Ok(crate::parse("1").unwrap()));
+        assert!(matches!(
+            res[2],
+            Err(ParseError::TruncatedRecord {
+                span: Span {
+                    start: 10,
+                    end: 12,
+                    ..
+                }
+            })
+        ));
+        assert_eq!(res[3], Ok(Element::String("ok".into())));
+        assert!(matches!(
+            res[4],
+            Err(ParseError::Tokenize(TokenizerError::UnexpectedEof { .. }))
+        ));
+        assert_eq!(res[5], Ok(Element::Null));
+        assert!(matches!(
+            res[6],
+            Err(ParseError::Tokenize(
+                TokenizerError::UnterminatedString { .. }
+            ))
+        ));
+        assert_eq!(res[7], Ok(Element::Boolean(true)));
+
+        // 区切りの前の値と、入力の終わりで切れたレコード
+        let res = records(b"1 2\n\x1e3\n\x1e[4,");
+        assert_eq!(res.len(), 3);
+        assert!(matches!(res[0], Err(ParseError::UnexpectedToken { .. })));
+        assert_eq!(res[1], Ok(crate::parse("3").unwrap()));
+        assert!(matches!(res[2], Err(ParseError::UnexpectedEof { .. })));
+
+        let res = records(b"\x1e5");
+        assert!(matches!(res[..], [Err(ParseError::TruncatedRecord { .. })]));
+    }
+}
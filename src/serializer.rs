@@ -0,0 +1,175 @@
+use std::{fmt, io};
+
+use crate::element::Element;
+use crate::number::Number;
+
+impl Element<'_> {
+    /// Writes the element as compact JSON.
+    pub fn to_writer<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        write!(writer, "{}", self)
+    }
+}
+
+impl fmt::Display for Element<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_element(f, self)
+    }
+}
+
+fn write_element<W: fmt::Write>(w: &mut W, element: &Element) -> fmt::Result {
+    match element {
+        Element::String(str) => write_string(w, str, false),
+        Element::Number(num) => write_number(w, num),
+        Element::Boolean(val) => write!(w, "{}", val),
+        Element::Null => w.write_str("null"),
+        Element::Object(object) => {
+            w.write_char('{')?;
+            for (i, member) in object.members().iter().enumerate() {
+                if i > 0 {
+                    w.write_char(',')?;
+                }
+                write_string(w, member.key(), false)?;
+                w.write_char(':')?;
+                write_element(w, member.value())?;
+            }
+            w.write_char('}')
+        }
+        Element::Array(array) => {
+            w.write_char('[')?;
+            for (i, element) in array.elements().iter().enumerate() {
+                if i > 0 {
+                    w.write_char(',')?;
+                }
+                write_element(w, element)?;
+            }
+            w.write_char(']')
+        }
+    }
+}
+
+pub(crate) fn write_number<W: fmt::Write>(w: &mut W, num: &Number) -> fmt::Result {
+    // NaNや無限大はJSONで表せないのでnullにする
+    match num.as_f64() {
+        Some(val) if !val.is_finite() => w.write_str("null"),
+        _ => write!(w, "{}", num),
+    }
+}
+
+// ascii_onlyの場合はASCII以外の文字も \uXXXX にエスケープする
+pub(crate) fn write_string<W: fmt::Write>(w: &mut W, str: &str, ascii_only: bool) -> fmt::Result {
+    w.write_char('"')?;
+
+    for c in str.chars() {
+        match c {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\u{8}' => w.write_str("\\b")?,
+            '\u{c}' => w.write_str("\\f")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            '\u{0}'..='\u{1f}' => write!(w, "\\u{:04x}", c as u32)?,
+            c if ascii_only && !c.is_ascii() => {
+                let mut buf = [0; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    write!(w, "\\u{:04x}", unit)?;
+                }
+            }
+            c => w.write_char(c)?,
+        }
+    }
+
+    w.write_char('"')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::element::{Array, Object};
+    use crate::parser::Parser;
+    use crate::test_util::Rng;
+    use crate::tokenizer::{Tokenizer, TokenizerOptions};
+
+    #[test]
+    fn serialize_scalar() {
+        assert_eq!(Element::Null.to_string(), "null");
+        assert_eq!(Element::Boolean(true).to_string(), "true");
+        assert_eq!(Element::Number(Number::from(-42)).to_string(), "-42");
+        assert_eq!(Element::Number(Number::from(2.5)).to_string(), "2.5");
+        assert_eq!(Element::Number(Number::from(f64::NAN)).to_string(), "null");
+        assert_eq!(
+            Element::String("\"こんにちは\"\\\n\u{1}".into()).to_string(),
+            r#""\"こんにちは\"\\\n\u0001""#
+        );
+    }
+
+    #[test]
+    fn serialize_nested() {
+        let element = Element::Object(Object::from_iter([
+            ("key".to_string(), Element::String("value".into())),
+            (
+                "array".to_string(),
+                Element::Array(Array::from_iter([
+                    Element::Number(Number::from(1)),
+                    Element::Null,
+                    Element::Object(Object::new()),
+                    Element::Array(Array::from_iter([])),
+                ])),
+            ),
+        ]));
+
+        assert_eq!(
+            element.to_string(),
+            r#"{"key":"value","array":[1,null,{},[]]}"#
+        );
+
+        let mut buf = vec![];
+        element.to_writer(&mut buf).unwrap();
+        assert_eq!(buf, element.to_string().into_bytes());
+    }
+
+    #[test]
+    fn round_trip_source() {
+        let source = r#"{"string":"こんにちは、世界！","integer":42,"float":3.1,"big":18446744073709551615,"negative_zero":-0,"exp":1e-7,"boolean":true,"null":null,"array":[1,"two",3.0,true,null],"escaped":"\"\\/\b\f\n\r\t\u0000😀"}"#;
+
+        let element = crate::parse(source).unwrap();
+
+        assert_eq!(crate::parse(&element.to_string()).unwrap(), element);
+        // -0.0 == 0.0 なので符号は文字列で確認する
+        assert!(element.to_string().contains(r#""negative_zero":-0.0"#));
+    }
+
+    #[test]
+    fn round_trip_lenient_arbitrary_precision() {
+        let options = TokenizerOptions {
+            allow_lenient_numbers: true,
+            arbitrary_precision: true,
+            ..TokenizerOptions::default()
+        };
+        let element = Parser::new(Tokenizer::with_options("[+1, .5, -.25e3]", options))
+            .parse()
+            .unwrap();
+
+        // 出力は厳密なJSONとして読み直せる
+        let json = element.to_string();
+        assert_eq!(json, "[1,0.5,-0.25e3]");
+        assert!(crate::parse(&json).is_ok());
+        assert_eq!(
+            Parser::new(Tokenizer::with_options(&json, options)).parse(),
+            Ok(element)
+        );
+    }
+
+    // parse(serialize(x)) == x がランダムな値で成り立つことを確認する
+    #[test]
+    fn round_trip_property() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..2_000 {
+            let element = rng.element(4);
+            let json = element.to_string();
+
+            assert_eq!(crate::parse(&json).as_ref(), Ok(&element), "{}", json);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt;
+
+/// Location of a token in the source text.
+///
+/// `start` and `end` are byte offsets, `line` and `column` are 1-based and
+/// point at the first character of the token.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn excerpt<'a>(&self, source: &'a str) -> Excerpt<'a> {
+        Excerpt {
+            source,
+            span: *self,
+        }
+    }
+}
+
+impl Default for Span {
+    fn default() -> Self {
+        Span {
+            start: 0,
+            end: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// The source line containing a span, underlined with carets.
+pub struct Excerpt<'a> {
+    source: &'a str,
+    span: Span,
+}
+
+impl fmt::Display for Excerpt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let start = floor_char_boundary(self.source, self.span.start);
+        let end = floor_char_boundary(self.source, self.span.end.max(start));
+
+        let line_start = self.source[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self.source[start..]
+            .find('\n')
+            .map_or(self.source.len(), |i| start + i);
+        let line = self.source[line_start..line_end].trim_end_matches('\r');
+
+        // タブはそのまま残し、全角文字は2列分の空白にして端末上の位置を揃える
+        let padding: String = self.source[line_start..start]
+            .chars()
+            .map(|c| match c {
+                '\t' => "\t",
+                c if is_wide(c) => "  ",
+                _ => " ",
+            })
+            .collect();
+        let width = self.source[start..end.min(line_end)]
+            .chars()
+            .map(|c| if is_wide(c) { 2 } else { 1 })
+            .sum::<usize>()
+            .max(1);
+
+        let number = self.span.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", number, line)?;
+        write!(f, "{} | {}{}", gutter, padding, "^".repeat(width))
+    }
+}
+
+// 端末で2列を占める東アジアの全角文字と絵文字 (主な範囲のみ)
+fn is_wide(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1100}'..='\u{115f}'
+            | '\u{2e80}'..='\u{303e}'
+            | '\u{3041}'..='\u{33ff}'
+            | '\u{3400}'..='\u{4dbf}'
+            | '\u{4e00}'..='\u{9fff}'
+            | '\u{a000}'..='\u{a4cf}'
+            | '\u{ac00}'..='\u{d7a3}'
+            | '\u{f900}'..='\u{faff}'
+            | '\u{fe30}'..='\u{fe4f}'
+            | '\u{ff00}'..='\u{ff60}'
+            | '\u{ffe0}'..='\u{ffe6}'
+            | '\u{1f300}'..='\u{1f64f}'
+            | '\u{1f680}'..='\u{1f6ff}'
+            | '\u{1f900}'..='\u{1f9ff}'
+            | '\u{1fa70}'..='\u{1faff}'
+            | '\u{20000}'..='\u{3fffd}'
+    )
+}
+
+fn floor_char_boundary(str: &str, index: usize) -> usize {
+    let mut index = index.min(str.len());
+
+    while !str.is_char_boundary(index) {
+        index -= 1;
+    }
+
+    index
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn excerpt() {
+        let source = "{\n  \"key\" \"value\"\n}";
+        let span = Span {
+            start: 10,
+            end: 17,
+            line: 2,
+            column: 9,
+        };
+
+        assert_eq!(
+            span.excerpt(source).to_string(),
+            "  |\n2 |   \"key\" \"value\"\n  |         ^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn excerpt_end_of_input() {
+        let source = "[1,";
+        let span = Span {
+            start: 3,
+            end: 3,
+            line: 1,
+            column: 4,
+        };
+
+        assert_eq!(span.excerpt(source).to_string(), "  |\n1 | [1,\n  |    ^");
+    }
+
+    #[test]
+    fn excerpt_tabs_and_wide_characters() {
+        let source = "{\n\t\"キー\" 1}";
+        let span = Span {
+            start: 12,
+            end: 13,
+            line: 2,
+            column: 7,
+        };
+
+        assert_eq!(
+            span.excerpt(source).to_string(),
+            "  |\n2 | \t\"キー\" 1}\n  | \t       ^"
+        );
+
+        let span = Span {
+            start: 4,
+            end: 10,
+            line: 2,
+            column: 3,
+        };
+        assert!(span.excerpt(source).to_string().ends_with("| \t ^^^^"));
+
+        // 絵文字も2列分として数える
+        let source = "[\"🚀🫠\" 1]";
+        let span = Span {
+            start: 12,
+            end: 13,
+            line: 1,
+            column: 6,
+        };
+        assert_eq!(
+            span.excerpt(source).to_string(),
+            "  |\n1 | [\"🚀🫠\" 1]\n  |         ^"
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use crate::element::Element;
+use crate::parser::{unexpected, ParseError, Parser, ParserOptions};
+use crate::span::Span;
+use crate::tokenizer::{Token, TokenizerError};
+
+// 次に読むもの
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum State {
+    Start,
+    NextElement,
+    Finished,
+}
+
+/// Yields the elements of a top-level array one at a time, so only the
+/// current element is held in memory.
+///
+/// The limits in `ParserOptions` count the whole document, the root array
+/// included.
+pub struct ArrayStream<I: Iterator> {
+    parser: Parser<I>,
+    state: State,
+    // これまでに返した要素の数
+    count: usize,
+}
+
+impl<'a, I> ArrayStream<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    pub fn new(tokens: I) -> Self {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    pub fn with_options(tokens: I, options: ParserOptions) -> Self {
+        ArrayStream {
+            parser: Parser::with_options(tokens, options),
+            state: State::Start,
+            count: 0,
+        }
+    }
+
+    fn next_element(&mut self) -> Result<Option<Element<'a>>, ParseError> {
+        let close = match self.state {
+            State::Start => {
+                match self.parser.next_token()? {
+                    (Token::LeftBracket, span) => {
+                        self.parser.count_node(span)?;
+                        self.parser.enter(span)?;
+                    }
+                    (token, span) => return Err(unexpected(token, span)),
+                }
+
+                *self.parser.peek_token()? == Token::RightBracket
+            }
+            State::NextElement => self.parser.parse_separator(Token::RightBracket)?,
+            State::Finished => return Ok(None),
+        };
+
+        // 閉じ括弧の後ろに何も残っていないことを確認して終わる
+        if close {
+            self.parser.next_token()?;
+            self.parser.leave();
+            self.parser.parse_end()?;
+            self.state = State::Finished;
+            return Ok(None);
+        }
+
+        self.count += 1;
+        self.parser.count_element(self.count)?;
+
+        let element = self.parser.parse_element()?;
+        self.state = State::NextElement;
+
+        Ok(Some(element))
+    }
+}
+
+impl<'a, I> Iterator for ArrayStream<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    type Item = Result<Element<'a>, ParseError>;
+
+    // エラーの後は何も返さない
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_element() {
+            Ok(element) => element.map(Ok),
+            Err(err) => {
+                self.state = State::Finished;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Yields each document of concatenated JSON such as `{"a":1}{"a":2}`
+/// together with the byte offset it starts at. See `Parser::into_documents`.
+///
+/// The limits in `ParserOptions` count each document separately.
+pub struct Documents<I: Iterator> {
+    parser: Parser<I>,
+    finished: bool,
+}
+
+impl<'a, I> Documents<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    pub(crate) fn new(parser: Parser<I>) -> Self {
+        Documents {
+            parser,
+            finished: false,
+        }
+    }
+}
+
+impl<'a, I> Iterator for Documents<I>
+where
+    I: Iterator<Item = Result<(Token<'a>, Span), TokenizerError>>,
+{
+    type Item = Result<(usize, Element<'a>), ParseError>;
+
+    // エラーの後は何も返さない
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished || self.parser.at_end() {
+            self.finished = true;
+            return None;
+        }
+
+        let offset = self.parser.peek_span().start;
+        self.parser.reset_nodes();
+
+        match self.parser.parse_element() {
+            Ok(element) => Some(Ok((offset, element))),
+            Err(err) => {
+                self.finished = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::limits::ParserLimits;
+    use crate::number::Number;
+    use crate::test_util::{Rng, Trickle};
+    use crate::tokenizer::Tokenizer;
+
+    #[test]
+    fn stream_elements() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let elements: Vec<Element> = (0..100).map(|_| rng.element(3)).collect();
+
+        let json = Element::Array(elements.iter().cloned().collect()).to_string();
+        let streamed: Result<Vec<Element>, ParseError> =
+            crate::parse_array_stream(Trickle(json.as_bytes())).collect();
+
+        assert_eq!(streamed, Ok(elements));
+
+        let mut stream = ArrayStream::new(Tokenizer::new(" [ ] "));
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn stream_errors() {
+        // 壊れた要素の手前までは返す
+        let mut stream = ArrayStream::new(Tokenizer::new(r#"[{"a": 1}, {"b": }, 3]"#));
+        assert_eq!(
+            stream.next(),
+            Some(Ok(crate::parse(r#"{"a": 1}"#).unwrap()))
+        );
+        assert!(matches!(
+            stream.next(),
+            Some(Err(ParseError::UnexpectedToken { .. }))
+        ));
+        assert_eq!(stream.next(), None);
+
+        let mut stream = ArrayStream::new(Tokenizer::new(r#"{"a": 1}"#));
+        assert!(matches!(
+            stream.next(),
+            Some(Err(ParseError::UnexpectedToken {
+                token: Token::LeftBrace,
+                ..
+            }))
+        ));
+        assert_eq!(stream.next(), None);
+
+        let mut stream = ArrayStream::new(Tokenizer::new("[1] 2"));
+        assert!(matches!(stream.next(), Some(Ok(_))));
+        assert!(matches!(
+            stream.next(),
+            Some(Err(ParseError::TrailingCharacters { .. }))
+        ));
+
+        let mut stream = ArrayStream::new(Tokenizer::new("[1, 2"));
+        assert_eq!(stream.by_ref().filter(Result::is_ok).count(), 2);
+        assert_eq!(stream.next(), None);
+
+        let options = ParserOptions {
+            limits: ParserLimits {
+                max_array_elements: Some(2),
+                ..ParserLimits::default()
+            },
+            ..ParserOptions::default()
+        };
+        let stream = ArrayStream::with_options(Tokenizer::new("[1, 2, 3]"), options);
+        assert!(matches!(
+            stream.last(),
+            Some(Err(ParseError::TooManyElements { limit: 2, .. }))
+        ));
+    }
+
+    #[test]
+    fn concatenated_documents() {
+        let input = "{\"a\":1}{\"a\":2} [3]\n\"x\"null";
+        let documents: Result<Vec<(usize, Element)>, ParseError> =
+            Parser::new(Tokenizer::new(input))
+                .into_documents()
+                .collect();
+
+        assert_eq!(
+            documents,
+            Ok(vec![
+                (0, crate::parse(r#"{"a":1}"#).unwrap()),
+                (7, crate::parse(r#"{"a":2}"#).unwrap()),
+                (15, crate::parse("[3]").unwrap()),
+                (19, Element::String("x".into())),
+                (22, Element::Null),
+            ])
+        );
+
+        let documents: Vec<_> = crate::parse_documents(Trickle(input.as_bytes())).collect();
+        assert_eq!(documents.len(), 5);
+        assert_eq!(
+            documents[2],
+            Ok((
+                15,
+                Element::Array([Element::Number(Number::from(3))].into_iter().collect())
+            ))
+        );
+
+        assert_eq!(
+            Parser::new(Tokenizer::new(" \n")).into_documents().next(),
+            None
+        );
+    }
+
+    #[test]
+    fn concatenated_document_errors() {
+        let mut documents = Parser::new(Tokenizer::new("{} {\"a\": }")).into_documents();
+        assert!(matches!(documents.next(), Some(Ok((0, _)))));
+        assert!(matches!(
+            documents.next(),
+            Some(Err(ParseError::UnexpectedToken { .. }))
+        ));
+        assert_eq!(documents.next(), None);
+
+        // 上限は文書ごとに数える
+        let options = ParserOptions {
+            limits: ParserLimits {
+                max_nodes: Some(2),
+                ..ParserLimits::default()
+            },
+            ..ParserOptions::default()
+        };
+        let mut documents =
+            Parser::with_options(Tokenizer::new("[1] [2] [3, 4]"), options).into_documents();
+        assert_eq!(documents.by_ref().filter(Result::is_ok).count(), 2);
+        assert_eq!(documents.next(), None);
+    }
+}
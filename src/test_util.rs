@@ -0,0 +1,82 @@
+use crate::{Array, Element, Number, Object};
+
+// 簡易な疑似乱数生成器(xorshift)
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    pub(crate) fn string(&mut self) -> String {
+        const CHARS: &[char] = &[
+            'a', 'z', 'A', '0', ' ', '"', '\\', '/', '\n', '\r', '\t', '\u{0}', '\u{8}', '\u{c}',
+            '\u{1f}', '\u{7f}', 'é', 'あ', '日', '本', '\u{ffff}', '😀',
+        ];
+
+        let len = self.below(8);
+
+        (0..len).map(|_| CHARS[self.below(CHARS.len())]).collect()
+    }
+
+    pub(crate) fn number(&mut self) -> Number {
+        match self.below(4) {
+            0 => Number::from(self.next()),
+            1 => Number::from(-((self.next() >> 1) as i64)),
+            2 => Number::from(self.below(1000) as f64 / 8.0),
+            _ => {
+                let val = f64::from_bits(self.next());
+                Number::from(if val.is_finite() { val } else { 0.5 })
+            }
+        }
+    }
+
+    // depthが0になるとスカラー値だけを生成する
+    pub(crate) fn element(&mut self, depth: usize) -> Element<'static> {
+        let kinds = if depth == 0 { 4 } else { 6 };
+
+        match self.below(kinds) {
+            0 => Element::String(self.string().into()),
+            1 => Element::Number(self.number()),
+            2 => Element::Boolean(self.below(2) == 0),
+            3 => Element::Null,
+            4 => {
+                let len = self.below(5);
+                Element::Array((0..len).map(|_| self.element(depth - 1)).collect::<Array>())
+            }
+            _ => {
+                let len = self.below(5);
+                Element::Object(
+                    (0..len)
+                        .map(|_| (self.string(), self.element(depth - 1)))
+                        .collect::<Object>(),
+                )
+            }
+        }
+    }
+}
+
+// 1回の読み込みで1バイトずつしか返さないリーダー
+pub(crate) struct Trickle<'a>(pub(crate) &'a [u8]);
+
+impl std::io::Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match (self.0.split_first(), buf.first_mut()) {
+            (Some((&byte, rest)), Some(slot)) => {
+                *slot = byte;
+                self.0 = rest;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+}
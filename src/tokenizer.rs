@@ -1,134 +1,741 @@
-use std::{iter::Peekable, str::Chars};
+use std::borrow::Cow;
+use std::{fmt, io};
+
+use crate::limits::{exceeded, TokenizerLimits};
+use crate::number::Number;
+use crate::reader::ReadBytes;
+use crate::span::Span;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum TokenizerError {
-    InvalidCharacter(char),
+    InvalidCharacter { character: char, span: Span },
+    InvalidNumber { text: String, span: Span },
+    // 文法は正しいがf64で表せない数値
+    NumberOutOfRange { text: String, span: Span },
+    UnterminatedString { span: Span },
+    InvalidEscape { character: char, span: Span },
+    InvalidUnicodeEscape { span: Span },
+    LoneSurrogate { code_unit: u16, span: Span },
+    ControlCharacter { character: char, span: Span },
+    UnexpectedEof { span: Span },
+    InputTooLarge { limit: usize, span: Span },
+    StringTooLong { limit: usize, span: Span },
+    NumberTooLong { limit: usize, span: Span },
+    InvalidUtf8 { span: Span },
+    Io { kind: io::ErrorKind, span: Span },
 }
 
+impl TokenizerError {
+    pub fn span(&self) -> Span {
+        match self {
+            TokenizerError::InvalidCharacter { span, .. }
+            | TokenizerError::InvalidNumber { span, .. }
+            | TokenizerError::NumberOutOfRange { span, .. }
+            | TokenizerError::UnterminatedString { span }
+            | TokenizerError::InvalidEscape { span, .. }
+            | TokenizerError::InvalidUnicodeEscape { span }
+            | TokenizerError::LoneSurrogate { span, .. }
+            | TokenizerError::ControlCharacter { span, .. }
+            | TokenizerError::UnexpectedEof { span }
+            | TokenizerError::InputTooLarge { span, .. }
+            | TokenizerError::StringTooLong { span, .. }
+            | TokenizerError::NumberTooLong { span, .. }
+            | TokenizerError::InvalidUtf8 { span }
+            | TokenizerError::Io { span, .. } => *span,
+        }
+    }
+}
+
+impl fmt::Display for TokenizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenizerError::InvalidCharacter { character, span } => {
+                write!(f, "invalid character {:?} at {}", character, span)
+            }
+            TokenizerError::InvalidNumber { text, span } => {
+                write!(f, "invalid number {:?} at {}", text, span)
+            }
+            TokenizerError::NumberOutOfRange { text, span } => {
+                write!(f, "number {:?} is out of range at {}", text, span)
+            }
+            TokenizerError::UnterminatedString { span } => {
+                write!(f, "unterminated string at {}", span)
+            }
+            TokenizerError::InvalidEscape { character, span } => {
+                write!(f, "invalid escape '\\{}' at {}", character, span)
+            }
+            TokenizerError::InvalidUnicodeEscape { span } => {
+                write!(f, "invalid unicode escape at {}", span)
+            }
+            TokenizerError::LoneSurrogate { code_unit, span } => {
+                write!(f, "lone surrogate \\u{:04X} at {}", code_unit, span)
+            }
+            TokenizerError::ControlCharacter { character, span } => {
+                write!(
+                    f,
+                    "unescaped control character U+{:04X} at {}",
+                    *character as u32, span
+                )
+            }
+            TokenizerError::UnexpectedEof { span } => {
+                write!(f, "unexpected end of input at {}", span)
+            }
+            TokenizerError::InputTooLarge { limit, span } => {
+                write!(f, "input exceeds {} bytes at {}", limit, span)
+            }
+            TokenizerError::StringTooLong { limit, span } => {
+                write!(f, "string exceeds {} bytes at {}", limit, span)
+            }
+            TokenizerError::NumberTooLong { limit, span } => {
+                write!(f, "number exceeds {} bytes at {}", limit, span)
+            }
+            TokenizerError::InvalidUtf8 { span } => {
+                write!(f, "invalid UTF-8 at {}", span)
+            }
+            TokenizerError::Io { kind, span } => {
+                write!(f, "failed to read input ({}) at {}", kind, span)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TokenizerError {}
+
 #[derive(Debug, PartialEq, Clone)]
-pub enum Token {
-    Number(f64),    // 数値
-    String(String), // 文字列
-    LeftBracket,    // 左括弧
-    RightBracket,   // 右括弧
-    Comma,          // カンマ
+pub enum Token<'a> {
+    Number(Number),       // 数値
+    String(Cow<'a, str>), // 文字列
+    LeftBracket,          // 左括弧
+    RightBracket,         // 右括弧
+    Comma,                // カンマ
     Bool(bool),
     Null,
     LeftBrace,
     RightBrace,
     Colon,
+    // JSON テキストシーケンスの区切り (0x1E)
+    RecordSeparator,
     End,
 }
 
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(num) => write!(f, "{}", num),
+            Token::String(str) => write!(f, "{:?}", str),
+            Token::LeftBracket => write!(f, "'['"),
+            Token::RightBracket => write!(f, "']'"),
+            Token::Comma => write!(f, "','"),
+            Token::Bool(val) => write!(f, "{}", val),
+            Token::Null => write!(f, "null"),
+            Token::LeftBrace => write!(f, "'{{'"),
+            Token::RightBrace => write!(f, "'}}'"),
+            Token::Colon => write!(f, "':'"),
+            Token::RecordSeparator => write!(f, "record separator"),
+            Token::End => write!(f, "end of input"),
+        }
+    }
+}
+
+impl Token<'_> {
+    pub fn into_owned(self) -> Token<'static> {
+        match self {
+            Token::Number(num) => Token::Number(num),
+            Token::String(str) => Token::String(Cow::Owned(str.into_owned())),
+            Token::LeftBracket => Token::LeftBracket,
+            Token::RightBracket => Token::RightBracket,
+            Token::Comma => Token::Comma,
+            Token::Bool(val) => Token::Bool(val),
+            Token::Null => Token::Null,
+            Token::LeftBrace => Token::LeftBrace,
+            Token::RightBrace => Token::RightBrace,
+            Token::Colon => Token::Colon,
+            Token::RecordSeparator => Token::RecordSeparator,
+            Token::End => Token::End,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct TokenizerOptions {
+    // `+1` や `.1` のような RFC 8259 の範囲外の数値を許可する
+    pub allow_lenient_numbers: bool,
+    // 数値を元のテキストのまま保持する
+    pub arbitrary_precision: bool,
+    // RFC 7464 のJSONテキストシーケンスとして 0x1E を区切りのトークンにする
+    pub json_seq: bool,
+    pub limits: TokenizerLimits,
+}
+
+// JSONテキストシーケンスのレコードの区切り
+const RECORD_SEPARATOR: u8 = 0x1E;
+
+// 入力元
+enum Source<'a> {
+    Slice(&'a [u8]),
+    Reader(ReadBytes<'a>),
+}
+
 pub struct Tokenizer<'a> {
-    chars: Peekable<Chars<'a>>,
+    source: Source<'a>,
+    // 読み込みに失敗したか、入力が上限を超えた場合のエラー
+    input_error: Option<TokenizerError>,
+    options: TokenizerOptions,
+    offset: usize,
+    line: usize,
+    column: usize,
+    // End かエラーを返した後はNoneを返す (json_seqの場合は読み込みのエラーの後だけ)
+    finished: bool,
 }
 
 pub trait TokenizerTrait<'a> {
-    fn tokenize(&mut self) -> Result<Vec<Token>, TokenizerError>;
+    fn tokenize(&mut self) -> Result<Vec<(Token<'a>, Span)>, TokenizerError>;
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(str: &'a str) -> Self {
-        let chars = str.chars().into_iter().peekable();
+        Self::with_options(str, TokenizerOptions::default())
+    }
 
-        Tokenizer { chars }
+    pub fn with_options(str: &'a str, options: TokenizerOptions) -> Self {
+        Self::from_slice_with_options(str.as_bytes(), options)
     }
-}
 
-impl TokenizerTrait<'_> for Tokenizer<'_> {
-    fn tokenize(&mut self) -> Result<Vec<Token>, TokenizerError> {
-        let mut tokens = Vec::new();
+    /// Tokenizes raw bytes that are not known to be UTF-8 yet. Only string
+    /// contents are validated, invalid sequences are reported with their
+    /// byte offset.
+    pub fn from_slice(bytes: &'a [u8]) -> Self {
+        Self::from_slice_with_options(bytes, TokenizerOptions::default())
+    }
 
-        while let Some(c) = self.chars.next() {
-            match c {
-                '0'..='9' | '+' | '-' | '.' => {
-                    let mut num = c.to_string();
+    pub fn from_slice_with_options(bytes: &'a [u8], options: TokenizerOptions) -> Self {
+        Self::from_source(Source::Slice(bytes), options)
+    }
 
-                    while let Some('0'..='9' | '.') = self.chars.peek() {
-                        num.push(self.chars.next().unwrap());
-                    }
+    /// Tokenizes UTF-8 text read from `reader`, holding only a fixed-size
+    /// buffer of it in memory.
+    pub fn from_reader<R: io::Read + 'a>(reader: R) -> Self {
+        Self::from_reader_with_options(reader, TokenizerOptions::default())
+    }
+
+    pub fn from_reader_with_options<R: io::Read + 'a>(
+        reader: R,
+        options: TokenizerOptions,
+    ) -> Self {
+        Self::from_source(Source::Reader(ReadBytes::new(reader)), options)
+    }
+
+    fn from_source(source: Source<'a>, options: TokenizerOptions) -> Self {
+        Tokenizer {
+            source,
+            input_error: None,
+            options,
+            offset: 0,
+            line: 1,
+            column: 1,
+            finished: false,
+        }
+    }
 
-                    tokens.push(Token::Number(num.parse::<f64>().unwrap()));
+    // 読み込みに失敗した場合や上限を超えるバイトは入力の終わりとして扱い、
+    // エラーを残しておく (bumpもここを通るので上限より先は読まない)
+    fn peek(&mut self) -> Option<u8> {
+        if self.input_error.is_some() {
+            return None;
+        }
+
+        let result = match &mut self.source {
+            Source::Slice(bytes) => Ok(bytes.get(self.offset).copied()),
+            Source::Reader(reader) => reader.peek(),
+        };
+
+        match result {
+            Ok(Some(_))
+                if exceeded(self.options.limits.max_input_bytes, self.offset + 1).is_some() =>
+            {
+                let limit = self.options.limits.max_input_bytes.unwrap_or_default();
+                self.input_error = Some(TokenizerError::InputTooLarge {
+                    limit,
+                    span: Span {
+                        end: self.offset + 1,
+                        ..self.current_span()
+                    },
+                });
+                None
+            }
+            Ok(byte) => byte,
+            Err(kind) => {
+                let span = self.current_span();
+                self.input_error = Some(TokenizerError::Io { kind, span });
+                None
+            }
+        }
+    }
+
+    // 1バイト進めて現在位置を更新する
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+
+        if let Source::Reader(reader) = &mut self.source {
+            reader.consume();
+        }
+
+        self.offset += 1;
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if !is_continuation(byte) {
+            // 列は文字単位で数える
+            self.column += 1;
+        }
+
+        Some(byte)
+    }
+
+    // 1文字分のバイトを読んでUTF-8として検証する
+    fn bump_char(&mut self) -> Result<char, TokenizerError> {
+        let start = self.current_span();
+        let mut buf = [0; 4];
+
+        let width = match self.bump() {
+            Some(lead) => {
+                buf[0] = lead;
+                utf8_width(lead)
+            }
+            None => return Err(TokenizerError::UnexpectedEof { span: start }),
+        };
+
+        for slot in buf.iter_mut().take(width).skip(1) {
+            match self.peek() {
+                Some(byte) if is_continuation(byte) => {
+                    self.bump();
+                    *slot = byte;
                 }
-                '"' => {
-                    let mut str = String::new();
-
-                    while let Some(c) = self.chars.next() {
-                        match c {
-                            '"' => {
-                                tokens.push(Token::String(str));
-                                break;
-                            }
-                            _ => str.push(c),
+                _ => break,
+            }
+        }
+
+        std::str::from_utf8(&buf[..width])
+            .ok()
+            .and_then(|str| str.chars().next())
+            .ok_or(TokenizerError::InvalidUtf8 {
+                span: self.span_from(start),
+            })
+    }
+
+    // 次の文字をInvalidCharacterとして返す
+    fn invalid_character(&mut self) -> TokenizerError {
+        let start = self.current_span();
+
+        match self.bump_char() {
+            Ok(character) => TokenizerError::InvalidCharacter {
+                character,
+                span: self.span_from(start),
+            },
+            Err(err) => err,
+        }
+    }
+
+    fn current_span(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn span_from(&self, start: Span) -> Span {
+        Span {
+            end: self.offset,
+            ..start
+        }
+    }
+
+    fn next_token(&mut self) -> Result<(Token<'a>, Span), TokenizerError> {
+        while let Some(b' ' | b'\n' | b'\r' | b'\t') = self.peek() {
+            self.bump();
+        }
+
+        let start = self.current_span();
+
+        let byte = match self.peek() {
+            Some(byte) => byte,
+            None => return Ok((Token::End, start)),
+        };
+
+        let token = match byte {
+            b'0'..=b'9' | b'+' | b'-' | b'.' => self.scan_number(start)?,
+            b'"' => self.scan_string(start)?,
+            b'n' => self.scan_keyword("null", Token::Null)?,
+            b't' => self.scan_keyword("true", Token::Bool(true))?,
+            b'f' => self.scan_keyword("false", Token::Bool(false))?,
+            RECORD_SEPARATOR if self.options.json_seq => {
+                self.bump();
+                Token::RecordSeparator
+            }
+            b'{' | b'}' | b':' | b'[' | b']' | b',' => {
+                self.bump();
+
+                match byte {
+                    b'{' => Token::LeftBrace,
+                    b'}' => Token::RightBrace,
+                    b':' => Token::Colon,
+                    b'[' => Token::LeftBracket,
+                    b']' => Token::RightBracket,
+                    _ => Token::Comma,
+                }
+            }
+            _ => return Err(self.invalid_character()),
+        };
+
+        Ok((token, self.span_from(start)))
+    }
+
+    fn scan_number(&mut self, start: Span) -> Result<Token<'a>, TokenizerError> {
+        let mut num = String::new();
+
+        // 数値に使われうる文字をまとめて読んでから文法を検証する
+        while let Some(byte @ (b'0'..=b'9' | b'.' | b'+' | b'-' | b'e' | b'E')) = self.peek() {
+            self.bump();
+            num.push(char::from(byte));
+
+            if let Some(limit) = exceeded(self.options.limits.max_number_length, num.len()) {
+                return Err(TokenizerError::NumberTooLong {
+                    limit,
+                    span: self.span_from(start),
+                });
+            }
+        }
+
+        if !is_valid_number(&num, self.options.allow_lenient_numbers) {
+            return Err(TokenizerError::InvalidNumber {
+                text: num,
+                span: self.span_from(start),
+            });
+        }
+
+        match Number::from_text(&num, self.options.arbitrary_precision) {
+            Some(val) => Ok(Token::Number(val)),
+            None => Err(TokenizerError::NumberOutOfRange {
+                text: num,
+                span: self.span_from(start),
+            }),
+        }
+    }
+
+    fn scan_string(&mut self, start: Span) -> Result<Token<'a>, TokenizerError> {
+        self.bump();
+
+        // スライスから読む場合はエスケープが現れるまで文字列を作らずに借用する
+        let content_start = self.offset;
+        let mut borrowed = match self.source {
+            Source::Slice(bytes) => Some(bytes),
+            Source::Reader(_) => None,
+        };
+        let mut str = String::new();
+
+        loop {
+            let char_start = self.current_span();
+
+            match self.peek_in_string(start)? {
+                b'"' => {
+                    let str = match borrowed {
+                        Some(bytes) => {
+                            Cow::Borrowed(self.utf8(&bytes[content_start..self.offset], start)?)
                         }
+                        None => Cow::Owned(str),
+                    };
+                    self.bump();
+                    return Ok(Token::String(str));
+                }
+                b'\\' => {
+                    if let Some(bytes) = borrowed.take() {
+                        str.push_str(self.utf8(&bytes[content_start..self.offset], start)?);
                     }
+                    self.bump();
+                    str.push(self.scan_escape(start, char_start)?);
                 }
-                'n' => {
-                    let required_chars = vec!['u', 'l', 'l'];
-
-                    for req in required_chars {
-                        if let Some(c) = self.chars.next() {
-                            if req != c {
-                                return Err(TokenizerError::InvalidCharacter(c));
-                            }
-                        } else {
-                            return Err(TokenizerError::InvalidCharacter(c));
-                        }
+                // 制御文字はエスケープされていなければならない
+                byte @ 0x00..=0x1f => {
+                    self.bump();
+                    return Err(TokenizerError::ControlCharacter {
+                        character: char::from(byte),
+                        span: self.span_from(char_start),
+                    });
+                }
+                // ASCII以外の文字だけUTF-8として検証する
+                byte if byte.is_ascii() => {
+                    self.bump();
+                    if borrowed.is_none() {
+                        str.push(char::from(byte));
                     }
-
-                    tokens.push(Token::Null);
                 }
-                '{' => tokens.push(Token::LeftBrace),
-                '}' => tokens.push(Token::RightBrace),
-                ':' => tokens.push(Token::Colon),
-                't' => {
-                    let mut str = String::new();
-                    let required_chars = ['r', 'u', 'e'];
-
-                    for &required_char in required_chars.iter() {
-                        if let Some(ch) = self.chars.peek() {
-                            if *ch == required_char {
-                                str.push(self.chars.next().unwrap());
-                            } else {
-                                return Err(TokenizerError::InvalidCharacter(c));
-                            }
-                        } else {
-                            return Err(TokenizerError::InvalidCharacter(c));
-                        }
+                _ => {
+                    let c = self.bump_char()?;
+                    if borrowed.is_none() {
+                        str.push(c);
                     }
+                }
+            }
+
+            let len = match borrowed {
+                Some(_) => self.offset - content_start,
+                None => str.len(),
+            };
+            if let Some(limit) = exceeded(self.options.limits.max_string_length, len) {
+                return Err(TokenizerError::StringTooLong {
+                    limit,
+                    span: self.span_from(start),
+                });
+            }
+        }
+    }
+
+    // 検証済みのバイト列を文字列として借用する
+    fn utf8(&self, bytes: &'a [u8], start: Span) -> Result<&'a str, TokenizerError> {
+        std::str::from_utf8(bytes).map_err(|_| TokenizerError::InvalidUtf8 {
+            span: self.span_from(start),
+        })
+    }
+
+    fn peek_in_string(&mut self, start: Span) -> Result<u8, TokenizerError> {
+        match self.peek() {
+            Some(byte) if !self.at_record_separator(byte) => Ok(byte),
+            _ => Err(TokenizerError::UnterminatedString {
+                span: self.span_from(start),
+            }),
+        }
+    }
 
-                    tokens.push(Token::Bool(true));
+    fn bump_in_string(&mut self, start: Span) -> Result<u8, TokenizerError> {
+        let byte = self.peek_in_string(start)?;
+        self.bump();
+
+        Ok(byte)
+    }
+
+    // バックスラッシュの後ろを読む
+    fn scan_escape(&mut self, start: Span, escape_start: Span) -> Result<char, TokenizerError> {
+        let c = match self.peek_in_string(start)? {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                self.bump();
+                return self.scan_unicode_escape(start, escape_start);
+            }
+            _ => {
+                let character = self.bump_char()?;
+                return Err(TokenizerError::InvalidEscape {
+                    character,
+                    span: self.span_from(escape_start),
+                });
+            }
+        };
+
+        self.bump();
+
+        Ok(c)
+    }
+
+    fn scan_unicode_escape(
+        &mut self,
+        start: Span,
+        escape_start: Span,
+    ) -> Result<char, TokenizerError> {
+        let lone_surrogate = |code_unit, span| TokenizerError::LoneSurrogate { code_unit, span };
+
+        let high = self.scan_hex4(start, escape_start)?;
+
+        let code = match high {
+            // 上位サロゲートの直後には下位サロゲートの \uXXXX が続く必要がある
+            0xD800..=0xDBFF => {
+                let low_start = self.current_span();
+
+                if self.peek() != Some(b'\\') {
+                    return Err(lone_surrogate(high, self.span_from(escape_start)));
                 }
-                'f' => {
-                    let mut str = String::new();
-                    let required_chars = ['a', 'l', 's', 'e'];
-
-                    for &required_char in required_chars.iter() {
-                        if let Some(ch) = self.chars.peek() {
-                            if *ch == required_char {
-                                str.push(self.chars.next().unwrap());
-                            } else {
-                                return Err(TokenizerError::InvalidCharacter(c));
-                            }
-                        } else {
-                            return Err(TokenizerError::InvalidCharacter(c));
-                        }
-                    }
+                self.bump();
+                if self.bump_in_string(start)? != b'u' {
+                    return Err(lone_surrogate(high, self.span_from(escape_start)));
+                }
+
+                let low = self.scan_hex4(start, low_start)?;
+
+                if !(0xDC00..=0xDFFF).contains(&low) {
+                    return Err(lone_surrogate(high, self.span_from(escape_start)));
+                }
+
+                0x10000 + ((u32::from(high) - 0xD800) << 10) + (u32::from(low) - 0xDC00)
+            }
+            0xDC00..=0xDFFF => return Err(lone_surrogate(high, self.span_from(escape_start))),
+            _ => u32::from(high),
+        };
+
+        char::from_u32(code).ok_or_else(|| lone_surrogate(high, self.span_from(escape_start)))
+    }
+
+    fn scan_hex4(&mut self, start: Span, escape_start: Span) -> Result<u16, TokenizerError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match char::from(self.bump_in_string(start)?).to_digit(16) {
+                Some(digit) => code = code * 16 + digit as u16,
+                None => {
+                    return Err(TokenizerError::InvalidUnicodeEscape {
+                        span: self.span_from(escape_start),
+                    })
+                }
+            }
+        }
+
+        Ok(code)
+    }
 
-                    tokens.push(Token::Bool(false));
+    // true, false, null を読む
+    fn scan_keyword(
+        &mut self,
+        keyword: &str,
+        token: Token<'a>,
+    ) -> Result<Token<'a>, TokenizerError> {
+        for required in keyword.bytes() {
+            match self.peek() {
+                Some(byte) if byte == required => {
+                    self.bump();
+                }
+                Some(byte) if !self.at_record_separator(byte) => {
+                    return Err(self.invalid_character())
                 }
-                '[' => tokens.push(Token::LeftBracket),
-                ']' => tokens.push(Token::RightBracket),
-                ',' => tokens.push(Token::Comma),
-                ' ' | '\n' | '\t' => {}
-                _ => return Err(TokenizerError::InvalidCharacter(c)),
+                _ => {
+                    return Err(TokenizerError::UnexpectedEof {
+                        span: self.current_span(),
+                    })
+                }
+            }
+        }
+
+        Ok(token)
+    }
+
+    // json_seqの場合は区切りをトークンの途中で現れた入力の終わりとして扱う
+    fn at_record_separator(&self, byte: u8) -> bool {
+        self.options.json_seq && byte == RECORD_SEPARATOR
+    }
+
+    // 次の区切りの直前まで読み飛ばす
+    fn skip_record(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte == RECORD_SEPARATOR {
+                break;
+            }
+            self.bump();
+        }
+    }
+}
+
+// UTF-8の2バイト目以降
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+// 先頭バイトから文字のバイト数を求める。先頭になれないバイトは0
+fn utf8_width(byte: u8) -> usize {
+    match byte {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => 0,
+    }
+}
+
+// number = [ minus ] int [ frac ] [ exp ]
+// int = zero / ( digit1-9 *DIGIT )
+// frac = decimal-point 1*DIGIT
+// exp = e [ minus / plus ] 1*DIGIT
+fn is_valid_number(num: &str, lenient: bool) -> bool {
+    let mut bytes = num.as_bytes();
+
+    let digits = |bytes: &[u8]| bytes.iter().take_while(|b| b.is_ascii_digit()).count();
+
+    match bytes.first() {
+        Some(b'-') => bytes = &bytes[1..],
+        Some(b'+') if lenient => bytes = &bytes[1..],
+        _ => {}
+    }
+
+    let int = digits(bytes);
+    if int == 0 && !(lenient && bytes.first() == Some(&b'.')) {
+        return false;
+    }
+    if int > 1 && bytes[0] == b'0' {
+        return false;
+    }
+    bytes = &bytes[int..];
+
+    if let Some(b'.') = bytes.first() {
+        let frac = digits(&bytes[1..]);
+        if frac == 0 {
+            return false;
+        }
+        bytes = &bytes[1 + frac..];
+    }
+
+    if let Some(b'e' | b'E') = bytes.first() {
+        bytes = &bytes[1..];
+        if let Some(b'+' | b'-') = bytes.first() {
+            bytes = &bytes[1..];
+        }
+        let exp = digits(bytes);
+        if exp == 0 {
+            return false;
+        }
+        bytes = &bytes[exp..];
+    }
+
+    bytes.is_empty()
+}
+
+/// Yields tokens one at a time, ending with `Token::End` or the first error.
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(Token<'a>, Span), TokenizerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let mut result = self.next_token();
+
+        // 読み込みに失敗していた場合は途中までのトークンよりそのエラーを優先する
+        if let Some(err) = self.input_error.take() {
+            result = Err(err);
+        }
+
+        match &result {
+            Ok((Token::End, _))
+            | Err(TokenizerError::Io { .. } | TokenizerError::InputTooLarge { .. }) => {
+                self.finished = true
             }
+            // 壊れたレコードの残りを読み飛ばして次のレコードから続ける
+            Err(_) if self.options.json_seq => self.skip_record(),
+            Err(_) => self.finished = true,
+            Ok(_) => {}
         }
 
-        tokens.push(Token::End);
+        Some(result)
+    }
+}
 
-        Ok(tokens)
+impl<'a> TokenizerTrait<'a> for Tokenizer<'a> {
+    fn tokenize(&mut self) -> Result<Vec<(Token<'a>, Span)>, TokenizerError> {
+        self.collect()
     }
 }
 
@@ -136,48 +743,688 @@ impl TokenizerTrait<'_> for Tokenizer<'_> {
 mod test {
     use super::*;
 
+    fn tokens(res: Vec<(Token, Span)>) -> Vec<Token> {
+        res.into_iter().map(|(token, _)| token).collect()
+    }
+
     #[test]
     fn number_tokenize() {
         let mut tokenizer = Tokenizer::new("1");
         let res = tokenizer.tokenize().unwrap();
-        assert_eq!(Token::Number(1.0), res[0]);
+        assert_eq!(Token::Number(Number::from(1)), res[0].0);
 
         let mut tokenizer = Tokenizer::new("-1");
         let res = tokenizer.tokenize().unwrap();
-        assert_eq!(Token::Number(-1.0), res[0]);
+        assert_eq!(Token::Number(Number::from(-1)), res[0].0);
 
-        let mut tokenizer = Tokenizer::new("+1");
+        let mut tokenizer = Tokenizer::new("1.6");
         let res = tokenizer.tokenize().unwrap();
-        assert_eq!(Token::Number(1.0), res[0]);
+        assert_eq!(Token::Number(Number::from(1.6)), res[0].0);
 
-        let mut tokenizer = Tokenizer::new(".1");
+        let mut tokenizer = Tokenizer::new("0");
         let res = tokenizer.tokenize().unwrap();
-        assert_eq!(Token::Number(0.1), res[0]);
+        assert_eq!(Token::Number(Number::from(0)), res[0].0);
 
-        let mut tokenizer = Tokenizer::new("1.6");
+        let mut tokenizer = Tokenizer::new("1e10");
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::Number(Number::from(1e10)), res[0].0);
+
+        let mut tokenizer = Tokenizer::new("-2.5E-3");
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::Number(Number::from(-2.5e-3)), res[0].0);
+
+        let mut tokenizer = Tokenizer::new("1E+2");
         let res = tokenizer.tokenize().unwrap();
-        assert_eq!(Token::Number(1.6), res[0]);
+        assert_eq!(Token::Number(Number::from(100.0)), res[0].0);
+    }
+
+    #[test]
+    fn large_integer_tokenize() {
+        let mut tokenizer = Tokenizer::new("[9007199254740993, -9223372036854775808]");
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(
+            Token::Number(Number::from(9_007_199_254_740_993u64)),
+            res[1].0
+        );
+        assert_eq!(Token::Number(Number::from(i64::MIN)), res[3].0);
+
+        let options = TokenizerOptions {
+            arbitrary_precision: true,
+            ..TokenizerOptions::default()
+        };
+        let mut tokenizer =
+            Tokenizer::with_options("3.14159265358979323846264338327950288", options);
+        let res = tokenizer.tokenize().unwrap();
+        match &res[0].0 {
+            Token::Number(num) => {
+                assert_eq!(num.as_str(), Some("3.14159265358979323846264338327950288"))
+            }
+            token => panic!("unexpected token {:?}", token),
+        }
+    }
+
+    #[test]
+    fn limits_tokenize() {
+        let options = |limits| TokenizerOptions {
+            limits,
+            ..TokenizerOptions::default()
+        };
+
+        let limits = TokenizerLimits {
+            max_input_bytes: Some(8),
+            ..TokenizerLimits::default()
+        };
+        let mut tokenizer = Tokenizer::with_options("[1, 2]  ", options(limits));
+        assert!(tokenizer.tokenize().is_ok());
+
+        let mut tokenizer = Tokenizer::with_options("[1, 2, 3]", options(limits));
+        assert_eq!(
+            Err(TokenizerError::InputTooLarge {
+                limit: 8,
+                span: Span {
+                    start: 8,
+                    end: 9,
+                    line: 1,
+                    column: 9
+                }
+            }),
+            tokenizer.tokenize()
+        );
+
+        let mut tokenizer = Tokenizer::with_options("[1, 2]   ", options(limits));
+        assert!(matches!(
+            tokenizer.tokenize(),
+            Err(TokenizerError::InputTooLarge { limit: 8, .. })
+        ));
+
+        // 文字列の長さはデコード後のバイト数で数える
+        let limits = TokenizerLimits {
+            max_string_length: Some(6),
+            ..TokenizerLimits::default()
+        };
+        let mut tokenizer = Tokenizer::with_options(r#"["日本", "\u0041BCDEF"]"#, options(limits));
+        assert!(tokenizer.tokenize().is_ok());
+
+        let mut tokenizer = Tokenizer::with_options(r#"["日本語"]"#, options(limits));
+        assert_eq!(
+            Err(TokenizerError::StringTooLong {
+                limit: 6,
+                span: Span {
+                    start: 1,
+                    end: 11,
+                    line: 1,
+                    column: 2
+                }
+            }),
+            tokenizer.tokenize()
+        );
+    }
+
+    #[test]
+    fn limits_reader_tokenize() {
+        // 読み込んだバイト数を数える
+        struct Counting<R>(R, usize);
+
+        impl<R: io::Read> io::Read for Counting<R> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                let n = self.0.read(buf)?;
+                self.1 += n;
+                Ok(n)
+            }
+        }
+
+        let options = TokenizerOptions {
+            limits: TokenizerLimits {
+                max_input_bytes: Some(1024),
+                ..TokenizerLimits::default()
+            },
+            ..TokenizerOptions::default()
+        };
+
+        // 終わらない文字列と数値も上限に達した時点で止まる
+        for prefix in [&b"[\""[..], &b"[1"[..]] {
+            let mut reader = Counting(io::Read::chain(prefix, io::repeat(b'1')), 0);
+            let res = Tokenizer::from_reader_with_options(&mut reader, options).tokenize();
+
+            assert_eq!(
+                Err(TokenizerError::InputTooLarge {
+                    limit: 1024,
+                    span: Span {
+                        start: 1024,
+                        end: 1025,
+                        line: 1,
+                        column: 1025
+                    }
+                }),
+                res
+            );
+            // 上限と読み込みバッファ1回分より多くは読まない
+            assert!(reader.1 <= 1024 + 8 * 1024, "read {} bytes", reader.1);
+        }
+
+        let options = TokenizerOptions {
+            limits: TokenizerLimits {
+                max_number_length: Some(4),
+                ..TokenizerLimits::default()
+            },
+            ..TokenizerOptions::default()
+        };
+        assert!(Tokenizer::with_options("[1234, -1.5]", options)
+            .tokenize()
+            .is_ok());
+        assert_eq!(
+            Err(TokenizerError::NumberTooLong {
+                limit: 4,
+                span: Span {
+                    start: 1,
+                    end: 6,
+                    line: 1,
+                    column: 2
+                }
+            }),
+            Tokenizer::from_reader_with_options(
+                io::Read::chain(&b"[1"[..], io::repeat(b'0')),
+                options
+            )
+            .tokenize()
+        );
+    }
+
+    #[test]
+    fn lenient_number_tokenize() {
+        let options = TokenizerOptions {
+            allow_lenient_numbers: true,
+            ..TokenizerOptions::default()
+        };
+
+        let mut tokenizer = Tokenizer::with_options("+1", options);
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::Number(Number::from(1)), res[0].0);
+
+        let mut tokenizer = Tokenizer::with_options(".1", options);
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::Number(Number::from(0.1)), res[0].0);
+
+        let mut tokenizer = Tokenizer::with_options("-.5e1", options);
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::Number(Number::from(-5.0)), res[0].0);
+
+        // 厳密モードではエラーになる
+        for num in ["+1", ".1", "-.5e1"] {
+            let mut tokenizer = Tokenizer::new(num);
+            assert!(matches!(
+                tokenizer.tokenize(),
+                Err(TokenizerError::InvalidNumber { .. })
+            ));
+        }
     }
 
     #[test]
     fn string_tokenize() {
         let mut tokenizer = Tokenizer::new("\"hello world\"");
         let res = tokenizer.tokenize().unwrap();
-        assert_eq!(Token::String("hello world".to_string()), res[0]);
+        assert_eq!(Token::String("hello world".into()), res[0].0);
+
+        let mut tokenizer = Tokenizer::new("\"hello");
+        let res = tokenizer.tokenize();
+        assert_eq!(
+            Err(TokenizerError::UnterminatedString {
+                span: Span {
+                    start: 0,
+                    end: 6,
+                    line: 1,
+                    column: 1
+                }
+            }),
+            res
+        );
+    }
+
+    #[test]
+    fn escaped_string_tokenize() {
+        let test_str = r#""\"quoted\" \\ \/ \b\f\n\r\t""#;
+        let mut tokenizer = Tokenizer::new(test_str);
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(
+            Token::String("\"quoted\" \\ / \u{8}\u{c}\n\r\t".into()),
+            res[0].0
+        );
+
+        let test_str = r#""\u3042\u00e9\u0041""#;
+        let mut tokenizer = Tokenizer::new(test_str);
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::String("あéA".into()), res[0].0);
+
+        // サロゲートペア
+        let test_str = r#""\uD83D\uDE00""#;
+        let mut tokenizer = Tokenizer::new(test_str);
+        let res = tokenizer.tokenize().unwrap();
+        assert_eq!(Token::String("😀".into()), res[0].0);
+    }
+
+    #[test]
+    fn invalid_escape_tokenize() {
+        let mut tokenizer = Tokenizer::new(r#""a\x""#);
+        assert_eq!(
+            Err(TokenizerError::InvalidEscape {
+                character: 'x',
+                span: Span {
+                    start: 2,
+                    end: 4,
+                    line: 1,
+                    column: 3
+                }
+            }),
+            tokenizer.tokenize()
+        );
+
+        let mut tokenizer = Tokenizer::new(r#""\u12G4""#);
+        assert_eq!(
+            Err(TokenizerError::InvalidUnicodeEscape {
+                span: Span {
+                    start: 1,
+                    end: 6,
+                    line: 1,
+                    column: 2
+                }
+            }),
+            tokenizer.tokenize()
+        );
+
+        let mut tokenizer = Tokenizer::new(r#""\uD83D""#);
+        assert_eq!(
+            Err(TokenizerError::LoneSurrogate {
+                code_unit: 0xD83D,
+                span: Span {
+                    start: 1,
+                    end: 7,
+                    line: 1,
+                    column: 2
+                }
+            }),
+            tokenizer.tokenize()
+        );
+
+        let mut tokenizer = Tokenizer::new(r#""\uD83D\u0041""#);
+        assert!(matches!(
+            tokenizer.tokenize(),
+            Err(TokenizerError::LoneSurrogate {
+                code_unit: 0xD83D,
+                ..
+            })
+        ));
+
+        let mut tokenizer = Tokenizer::new(r#""\uDE00""#);
+        assert!(matches!(
+            tokenizer.tokenize(),
+            Err(TokenizerError::LoneSurrogate {
+                code_unit: 0xDE00,
+                ..
+            })
+        ));
+
+        let mut tokenizer = Tokenizer::new("\"a\tb\"");
+        assert_eq!(
+            Err(TokenizerError::ControlCharacter {
+                character: '\t',
+                span: Span {
+                    start: 2,
+                    end: 3,
+                    line: 1,
+                    column: 3
+                }
+            }),
+            tokenizer.tokenize()
+        );
+
+        let mut tokenizer = Tokenizer::new(r#""abc\"#);
+        assert!(matches!(
+            tokenizer.tokenize(),
+            Err(TokenizerError::UnterminatedString { .. })
+        ));
+    }
+
+    #[test]
+    fn invalid_number_tokenize() {
+        let mut tokenizer = Tokenizer::new("1.2.3");
+        let res = tokenizer.tokenize();
+        assert_eq!(
+            Err(TokenizerError::InvalidNumber {
+                text: "1.2.3".to_string(),
+                span: Span {
+                    start: 0,
+                    end: 5,
+                    line: 1,
+                    column: 1
+                }
+            }),
+            res
+        );
+
+        for num in ["01", "-01", "1.", "1.e5", "1e", "1e+", "--1", "1-2"] {
+            let mut tokenizer = Tokenizer::new(num);
+            assert_eq!(
+                Err(TokenizerError::InvalidNumber {
+                    text: num.to_string(),
+                    span: Span {
+                        start: 0,
+                        end: num.len(),
+                        line: 1,
+                        column: 1
+                    }
+                }),
+                tokenizer.tokenize()
+            );
+        }
+
+        // 文法は正しいがf64の範囲を超える数値は別のエラーにする
+        for num in ["1e999", "-1e999"] {
+            assert_eq!(
+                Err(TokenizerError::NumberOutOfRange {
+                    text: num.to_string(),
+                    span: Span {
+                        start: 0,
+                        end: num.len(),
+                        line: 1,
+                        column: 1
+                    }
+                }),
+                Tokenizer::new(num).tokenize()
+            );
+        }
+        assert!(Tokenizer::with_options(
+            "1e999",
+            TokenizerOptions {
+                arbitrary_precision: true,
+                ..TokenizerOptions::default()
+            }
+        )
+        .tokenize()
+        .is_ok());
+
+        let mut tokenizer = Tokenizer::new("-");
+        let res = tokenizer.tokenize();
+        assert_eq!(
+            Err(TokenizerError::InvalidNumber {
+                text: "-".to_string(),
+                span: Span {
+                    start: 0,
+                    end: 1,
+                    line: 1,
+                    column: 1
+                }
+            }),
+            res
+        );
+    }
+
+    #[test]
+    fn token_span() {
+        let test_str = "{\n  \"キー\": [true, null]\n}";
+        let mut tokenizer = Tokenizer::new(test_str);
+        let res = tokenizer.tokenize().unwrap();
+
+        let spans: Vec<(usize, usize, usize, usize)> = res
+            .iter()
+            .map(|(_, span)| (span.start, span.end, span.line, span.column))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (0, 1, 1, 1),
+                (4, 12, 2, 3),
+                (12, 13, 2, 7),
+                (14, 15, 2, 9),
+                (15, 19, 2, 10),
+                (19, 20, 2, 14),
+                (21, 25, 2, 16),
+                (25, 26, 2, 20),
+                (27, 28, 3, 1),
+                (28, 28, 3, 2),
+            ],
+            spans
+        );
+    }
+
+    #[test]
+    fn reader_tokenize() {
+        use crate::test_util::Trickle;
+
+        let test_str = "{\n  \"キー\": [\"こんにちは😀\", 1.5]\n}";
+        let expected = Tokenizer::new(test_str).tokenize().unwrap();
+
+        let res = Tokenizer::from_reader(test_str.as_bytes()).tokenize();
+        assert_eq!(res.as_ref(), Ok(&expected));
+
+        let res = Tokenizer::from_reader(Trickle(test_str.as_bytes())).tokenize();
+        assert_eq!(res.as_ref(), Ok(&expected));
+    }
+
+    #[test]
+    fn reader_error_tokenize() {
+        struct Failing;
+
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::BrokenPipe, "broken"))
+            }
+        }
+
+        // 読み込みのエラーは数値などのトークンより優先される
+        let res = Tokenizer::from_reader(io::Read::chain(&b"12"[..], Failing)).tokenize();
+        assert_eq!(
+            Err(TokenizerError::Io {
+                kind: io::ErrorKind::BrokenPipe,
+                span: Span {
+                    start: 2,
+                    end: 2,
+                    line: 1,
+                    column: 3
+                }
+            }),
+            res
+        );
+    }
+
+    #[test]
+    fn slice_tokenize() {
+        let res = Tokenizer::from_slice("[\"キー\", true]".as_bytes()).tokenize();
+        assert_eq!(
+            vec![
+                Token::LeftBracket,
+                Token::String("キー".into()),
+                Token::Comma,
+                Token::Bool(true),
+                Token::RightBracket,
+                Token::End
+            ],
+            tokens(res.unwrap())
+        );
+
+        let res = Tokenizer::from_slice(b"{\"key\": \"a\xff\"}").tokenize();
+        assert_eq!(
+            Err(TokenizerError::InvalidUtf8 {
+                span: Span {
+                    start: 10,
+                    end: 11,
+                    line: 1,
+                    column: 11
+                }
+            }),
+            res
+        );
+
+        // 不正なバイト列は文字列の外でも文字にはならない
+        let res = Tokenizer::from_slice(b"[\xe3\x81]").tokenize();
+        assert_eq!(
+            Err(TokenizerError::InvalidUtf8 {
+                span: Span {
+                    start: 1,
+                    end: 3,
+                    line: 1,
+                    column: 2
+                }
+            }),
+            res
+        );
+
+        let res = Tokenizer::from_slice("[あ]".as_bytes()).tokenize();
+        assert_eq!(
+            Err(TokenizerError::InvalidCharacter {
+                character: 'あ',
+                span: Span {
+                    start: 1,
+                    end: 4,
+                    line: 1,
+                    column: 2
+                }
+            }),
+            res
+        );
+
+        // サロゲート、冗長な表現、途中で終わる文字
+        for bytes in [
+            &b"\"\xed\xa0\x80\""[..],
+            b"\"\xc0\xaf\"",
+            b"\"\xf0\x9f\x98\"",
+            b"\"\x80\"",
+            b"\"\xe3\x81",
+        ] {
+            let res = Tokenizer::from_slice(bytes).tokenize();
+            assert!(
+                matches!(res, Err(TokenizerError::InvalidUtf8 { span }) if span.start == 1),
+                "{:?}",
+                bytes
+            );
+        }
+
+        let res = Tokenizer::from_reader(&b"[\"a\xff\"]"[..]).tokenize();
+        assert!(matches!(res, Err(TokenizerError::InvalidUtf8 { .. })));
+    }
+
+    #[test]
+    fn borrowed_strings() {
+        let test_str = r#"["キー", "a\nb", "\u3042い", ""]"#;
+        let res = Tokenizer::new(test_str).tokenize().unwrap();
+
+        // エスケープを含まない文字列は入力を借用する
+        assert!(matches!(&res[1].0, Token::String(Cow::Borrowed("キー"))));
+        assert!(matches!(&res[3].0, Token::String(Cow::Owned(str)) if str == "a\nb"));
+        assert!(matches!(&res[5].0, Token::String(Cow::Owned(str)) if str == "あい"));
+        assert!(matches!(&res[7].0, Token::String(Cow::Borrowed(""))));
+
+        // リーダーからは常に所有した文字列になる
+        let res = Tokenizer::from_reader(test_str.as_bytes())
+            .tokenize()
+            .unwrap();
+        assert!(matches!(&res[1].0, Token::String(Cow::Owned(str)) if str == "キー"));
+        assert!(matches!(&res[5].0, Token::String(Cow::Owned(str)) if str == "あい"));
+
+        let options = TokenizerOptions {
+            limits: TokenizerLimits {
+                max_string_length: Some(3),
+                ..TokenizerLimits::default()
+            },
+            ..TokenizerOptions::default()
+        };
+        let res = Tokenizer::with_options(r#""abc" "abcd""#, options).tokenize();
+        assert!(matches!(
+            res,
+            Err(TokenizerError::StringTooLong { limit: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn crlf_tokenize() {
+        let res = Tokenizer::from_slice(b"{\r\n\t\"a\":\r1\r\n}\r\n").tokenize();
+        assert_eq!(
+            vec![
+                Token::LeftBrace,
+                Token::String("a".into()),
+                Token::Colon,
+                Token::Number(Number::from(1)),
+                Token::RightBrace,
+                Token::End
+            ],
+            tokens(res.unwrap())
+        );
+
+        // \r は行を変えない
+        let res = Tokenizer::new("[\r\n\r1]").tokenize().unwrap();
+        assert_eq!(
+            Span {
+                start: 4,
+                end: 5,
+                line: 2,
+                column: 2
+            },
+            res[1].1
+        );
+    }
+
+    #[test]
+    fn iterate_tokens() {
+        let mut tokenizer = Tokenizer::new("[1]");
+        assert_eq!(tokenizer.next().unwrap().unwrap().0, Token::LeftBracket);
+        assert_eq!(
+            tokenizer.next().unwrap().unwrap().0,
+            Token::Number(Number::from(1))
+        );
+        assert_eq!(tokenizer.next().unwrap().unwrap().0, Token::RightBracket);
+        assert_eq!(tokenizer.next().unwrap().unwrap().0, Token::End);
+        assert_eq!(tokenizer.next(), None);
+
+        // エラーの後は何も返さない
+        let mut tokenizer = Tokenizer::new("[@, 1]");
+        assert_eq!(tokenizer.next().unwrap().unwrap().0, Token::LeftBracket);
+        assert!(tokenizer.next().unwrap().is_err());
+        assert_eq!(tokenizer.next(), None);
+    }
+
+    #[test]
+    fn invalid_keyword_tokenize() {
+        let mut tokenizer = Tokenizer::new("[tru]");
+        let res = tokenizer.tokenize();
+        assert_eq!(
+            Err(TokenizerError::InvalidCharacter {
+                character: ']',
+                span: Span {
+                    start: 4,
+                    end: 5,
+                    line: 1,
+                    column: 5
+                }
+            }),
+            res
+        );
+
+        let mut tokenizer = Tokenizer::new("nul");
+        let res = tokenizer.tokenize();
+        assert_eq!(
+            Err(TokenizerError::UnexpectedEof {
+                span: Span {
+                    start: 3,
+                    end: 3,
+                    line: 1,
+                    column: 4
+                }
+            }),
+            res
+        );
     }
 
     #[test]
     fn array_tokenize() {
         let test_str = r#"["文字列1", "文字列2"]"#;
         let mut tokenizer = Tokenizer::new(test_str);
-        let res = tokenizer.tokenize().unwrap();
+        let res = tokens(tokenizer.tokenize().unwrap());
 
         assert_eq!(
             vec![
                 Token::LeftBracket,
-                Token::String("文字列1".to_string()),
+                Token::String("文字列1".into()),
                 Token::Comma,
-                Token::String("文字列2".to_string()),
+                Token::String("文字列2".into()),
                 Token::RightBracket,
                 Token::End
             ],
@@ -188,13 +1435,13 @@ mod test {
     fn boolean_tokenize() {
         let test_str = r#"true"#;
         let mut tokenizer = Tokenizer::new(test_str);
-        let res = tokenizer.tokenize().unwrap();
+        let res = tokens(tokenizer.tokenize().unwrap());
 
         assert_eq!(vec![Token::Bool(true), Token::End], res);
 
         let test_str = r#"false"#;
         let mut tokenizer = Tokenizer::new(test_str);
-        let res = tokenizer.tokenize().unwrap();
+        let res = tokens(tokenizer.tokenize().unwrap());
 
         assert_eq!(vec![Token::Bool(false), Token::End], res);
     }
@@ -203,7 +1450,7 @@ mod test {
     fn null_tokenize() {
         let test_str = r#"null"#;
         let mut tokenizer = Tokenizer::new(test_str);
-        let res = tokenizer.tokenize().unwrap();
+        let res = tokens(tokenizer.tokenize().unwrap());
 
         assert_eq!(vec![Token::Null, Token::End], res);
     }
@@ -216,14 +1463,14 @@ mod test {
           }
         "#;
         let mut tokenizer = Tokenizer::new(test_str);
-        let res = tokenizer.tokenize().unwrap();
+        let res = tokens(tokenizer.tokenize().unwrap());
 
         assert_eq!(
             vec![
                 Token::LeftBrace,
-                Token::String("key".to_string()),
+                Token::String("key".into()),
                 Token::Colon,
-                Token::String("value".to_string()),
+                Token::String("value".into()),
                 Token::RightBrace,
                 Token::End
             ],
@@ -263,62 +1510,62 @@ mod test {
       "#;
 
         let mut tokenizer = Tokenizer::new(test_str);
-        let res = tokenizer.tokenize().unwrap();
+        let res = tokens(tokenizer.tokenize().unwrap());
 
         assert_eq!(
             vec![
                 Token::LeftBrace,
-                Token::String("string".to_string()),
+                Token::String("string".into()),
                 Token::Colon,
-                Token::String("こんにちは、世界！".to_string()),
+                Token::String("こんにちは、世界！".into()),
                 Token::Comma,
-                Token::String("integer".to_string()),
+                Token::String("integer".into()),
                 Token::Colon,
-                Token::Number(42.0),
+                Token::Number(Number::from(42)),
                 Token::Comma,
-                Token::String("float".to_string()),
+                Token::String("float".into()),
                 Token::Colon,
-                Token::Number(3.1),
+                Token::Number(Number::from(3.1)),
                 Token::Comma,
-                Token::String("boolean".to_string()),
+                Token::String("boolean".into()),
                 Token::Colon,
                 Token::Bool(true),
                 Token::Comma,
-                Token::String("null".to_string()),
+                Token::String("null".into()),
                 Token::Colon,
                 Token::Null,
                 Token::Comma,
-                Token::String("array".to_string()),
+                Token::String("array".into()),
                 Token::Colon,
                 Token::LeftBracket,
-                Token::Number(1.0),
+                Token::Number(Number::from(1)),
                 Token::Comma,
-                Token::String("two".to_string()),
+                Token::String("two".into()),
                 Token::Comma,
-                Token::Number(3.0),
+                Token::Number(Number::from(3.0)),
                 Token::Comma,
                 Token::Bool(true),
                 Token::Comma,
                 Token::Null,
                 Token::RightBracket,
                 Token::Comma,
-                Token::String("nested_array".to_string()),
+                Token::String("nested_array".into()),
                 Token::Colon,
                 Token::LeftBracket,
                 Token::LeftBracket,
-                Token::String("a".to_string()),
+                Token::String("a".into()),
                 Token::Comma,
-                Token::String("b".to_string()),
+                Token::String("b".into()),
                 Token::Comma,
-                Token::String("c".to_string()),
+                Token::String("c".into()),
                 Token::RightBracket,
                 Token::Comma,
                 Token::LeftBracket,
-                Token::Number(1.0),
+                Token::Number(Number::from(1)),
                 Token::Comma,
-                Token::Number(2.0),
+                Token::Number(Number::from(2)),
                 Token::Comma,
-                Token::Number(3.0),
+                Token::Number(Number::from(3)),
                 Token::RightBracket,
                 Token::Comma,
                 Token::LeftBracket,
@@ -330,61 +1577,126 @@ mod test {
                 Token::RightBracket,
                 Token::RightBracket,
                 Token::Comma,
-                Token::String("object".to_string()),
+                Token::String("object".into()),
                 Token::Colon,
                 Token::LeftBrace,
-                Token::String("name".to_string()),
+                Token::String("name".into()),
                 Token::Colon,
-                Token::String("山田太郎".to_string()),
+                Token::String("山田太郎".into()),
                 Token::Comma,
-                Token::String("age".to_string()),
+                Token::String("age".into()),
                 Token::Colon,
-                Token::Number(30.0),
+                Token::Number(Number::from(30)),
                 Token::Comma,
-                Token::String("address".to_string()),
+                Token::String("address".into()),
                 Token::Colon,
                 Token::LeftBrace,
-                Token::String("country".to_string()),
+                Token::String("country".into()),
                 Token::Colon,
-                Token::String("日本".to_string()),
+                Token::String("日本".into()),
                 Token::Comma,
-                Token::String("city".to_string()),
+                Token::String("city".into()),
                 Token::Colon,
-                Token::String("東京".to_string()),
+                Token::String("東京".into()),
                 Token::Comma,
-                Token::String("postal_code".to_string()),
+                Token::String("postal_code".into()),
                 Token::Colon,
-                Token::String("123-4567".to_string()),
+                Token::String("123-4567".into()),
                 Token::RightBrace,
                 Token::Comma,
-                Token::String("hobbies".to_string()),
+                Token::String("hobbies".into()),
                 Token::Colon,
                 Token::LeftBracket,
-                Token::String("読書".to_string()),
+                Token::String("読書".into()),
                 Token::Comma,
-                Token::String("映画".to_string()),
+                Token::String("映画".into()),
                 Token::Comma,
-                Token::String("旅行".to_string()),
+                Token::String("旅行".into()),
                 Token::RightBracket,
                 Token::RightBrace,
                 Token::Comma,
-                Token::String("empty_array".to_string()),
+                Token::String("empty_array".into()),
                 Token::Colon,
                 Token::LeftBracket,
                 Token::RightBracket,
                 Token::Comma,
-                Token::String("empty_object".to_string()),
+                Token::String("empty_object".into()),
                 Token::Colon,
                 Token::LeftBrace,
                 Token::RightBrace,
                 Token::Comma,
-                Token::String("unicode".to_string()),
+                Token::String("unicode".into()),
                 Token::Colon,
-                Token::String(r#"\u3053\u3093\u306B\u3061\u306F"#.to_string()),
+                Token::String("こんにちは".into()),
                 Token::RightBrace,
                 Token::End
             ],
             res
         );
     }
+
+    #[test]
+    fn json_seq_tokenize() {
+        let input = b"\x1e[1]\n\x1e\"ab\x1etru\x1e@\x1enull\n";
+
+        // 既定では区切りは不正な文字になる
+        let res = Tokenizer::from_slice(input).tokenize();
+        assert!(matches!(
+            res,
+            Err(TokenizerError::InvalidCharacter {
+                character: '\u{1e}',
+                ..
+            })
+        ));
+
+        let options = TokenizerOptions {
+            json_seq: true,
+            ..TokenizerOptions::default()
+        };
+
+        // エラーの後は次の区切りから続ける
+        let res: Vec<_> = Tokenizer::from_slice_with_options(input, options)
+            .map(|res| res.map(|(token, _)| token))
+            .collect();
+        assert_eq!(
+            vec![
+                Ok(Token::RecordSeparator),
+                Ok(Token::LeftBracket),
+                Ok(Token::Number(Number::from(1))),
+                Ok(Token::RightBracket),
+                Ok(Token::RecordSeparator),
+                Err(TokenizerError::UnterminatedString {
+                    span: Span {
+                        start: 6,
+                        end: 9,
+                        line: 2,
+                        column: 2
+                    }
+                }),
+                Ok(Token::RecordSeparator),
+                Err(TokenizerError::UnexpectedEof {
+                    span: Span {
+                        start: 13,
+                        end: 13,
+                        line: 2,
+                        column: 9
+                    }
+                }),
+                Ok(Token::RecordSeparator),
+                Err(TokenizerError::InvalidCharacter {
+                    character: '@',
+                    span: Span {
+                        start: 14,
+                        end: 15,
+                        line: 2,
+                        column: 10
+                    }
+                }),
+                Ok(Token::RecordSeparator),
+                Ok(Token::Null),
+                Ok(Token::End),
+            ],
+            res
+        );
+    }
 }